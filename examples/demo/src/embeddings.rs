@@ -16,7 +16,7 @@ pub async fn embeddings() -> Result<(), Box<dyn Error>> {
                     "Make huge profits without work, earn up to $100,000 a day"];
 
     let documents: Vec<Document> = data.iter().enumerate().map(|(i, x)| {
-        Document { id: i.to_string(), text: x.to_string() }
+        Document::new(&i.to_string(), x)
     }).collect();
 
     println!("Running similarity queries");
@@ -45,7 +45,7 @@ pub async fn embeddings() -> Result<(), Box<dyn Error>> {
 
     data[0] = "See it: baby panda born";
 
-    let updates: Vec<Document> = vec![Document {id: "0".to_string(), text: data[0].to_string()}];
+    let updates: Vec<Document> = vec![Document::new("0", data[0])];
     embeddings.delete(&["5"].to_vec()).await?;
     embeddings.add(&updates).await?;
     embeddings.upsert().await?;
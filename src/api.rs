@@ -1,23 +1,31 @@
 use enum_as_inner::EnumAsInner;
-use reqwest::{multipart, RequestBuilder, Response};
+use reqwest::{multipart, Client, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde_json::Value;
-use std::env;
-use std::error::Error;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+pub use crate::builder::ClientBuilder;
+pub use crate::error::{Result, TxtaiError};
+pub use crate::retry::RetryPolicy;
 
 // Generic return types
-pub type APIResponse = Result<Response, Box<dyn Error>>;
-pub type IndexResults = Result<Vec<IndexResult>, Box<dyn Error>>;
-pub type IndexResultsBatch = Result<Vec<Vec<IndexResult>>, Box<dyn Error>>;
-pub type Strings = Result<String, Box<dyn Error>>;
-pub type StringsBatch = Result<Vec<String>, Box<dyn Error>>;
-pub type Texts = Result<Text, Box<dyn Error>>;
-pub type TextsBatch = Result<Vec<Text>, Box<dyn Error>>;
-
-/// Base API definition
+pub type APIResponse = Result<Response>;
+pub type IndexResults = Result<Vec<IndexResult>>;
+pub type IndexResultsBatch = Result<Vec<Vec<IndexResult>>>;
+pub type Strings = Result<String>;
+pub type StringsBatch = Result<Vec<String>>;
+pub type Texts = Result<Text>;
+pub type TextsBatch = Result<Vec<Text>>;
+
+/// Base API definition. Cloning an API is cheap and the clone shares the same
+/// connection pool.
+#[derive(Clone)]
 pub struct API {
-    url: String,
-    token: String
+    config: ClientBuilder,
+    client: Client
 }
 
 /// Base API implementation
@@ -25,9 +33,9 @@ impl API {
     /// Creates an API instance.
     ///
     pub fn new() -> API {
-        API { 
-            url: env::var("TXTAI_API_URL").unwrap_or(String::from("")),
-            token: env::var("TXTAI_API_TOKEN").unwrap_or(String::from(""))
+        API {
+            config: ClientBuilder::new(),
+            client: API::shared()
         }
     }
 
@@ -36,9 +44,9 @@ impl API {
     /// # Arguments
     /// * `url` - API url
     pub fn with_url(url: &str) -> API {
-        API { 
-            url: url.to_string(),
-            token: env::var("TXTAI_API_TOKEN").unwrap_or(String::from(""))
+        API {
+            config: ClientBuilder::new().url(url),
+            client: API::shared()
         }
     }
 
@@ -48,50 +56,283 @@ impl API {
     /// * `url` - API url
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> API {
-        API { 
-            url: url.to_string(),
-            token: token.to_string()
+        API {
+            config: ClientBuilder::new().url(url).token(token),
+            client: API::shared()
+        }
+    }
+
+    /// Creates an API instance with a dedicated connection pool. Returns an error if the
+    /// HTTP client can't be initialized.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    /// * `token` - API token
+    /// * `pool` - connection pool settings
+    pub fn with_pool(url: &str, token: &str, pool: &Pool) -> Result<API> {
+        ClientBuilder::new().url(url).token(token).pool(pool.clone()).build()
+    }
+
+    /// Creates a ClientBuilder for configuring a new API instance.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Creates an API instance from builder settings and the client built from them.
+    ///
+    /// # Arguments
+    /// * `config` - builder settings
+    /// * `client` - HTTP client
+    pub(crate) fn from_builder(config: ClientBuilder, client: Client) -> API {
+        API {
+            config,
+            client
         }
     }
 
-    /// Executes a GET request. Returns Response.
+    /// Sets the retry policy used for all requests.
+    ///
+    /// # Arguments
+    /// * `retry` - retry policy
+    pub fn retry(mut self, retry: RetryPolicy) -> API {
+        self.config.retry = retry;
+        self
+    }
+
+    /// Sets the timeouts used for all requests. Changing the connect timeout creates a
+    /// dedicated connection pool for this API instance. Returns an error if the HTTP client
+    /// can't be initialized.
+    ///
+    /// # Arguments
+    /// * `timeouts` - timeout settings
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Result<API> {
+        let connect = timeouts.connect != self.config.timeouts.connect;
+
+        self.config.timeouts = timeouts;
+        if connect {
+            self.client = self.config.client()?;
+        }
+
+        Ok(self)
+    }
+
+    /// Sets the request timeout, replacing the configured read, request and method timeouts.
+    /// This is typically used with a clone to change the timeout for a single call.
+    ///
+    /// # Arguments
+    /// * `timeout` - request timeout
+    pub fn timeout(mut self, timeout: Duration) -> API {
+        self.config.timeouts.read = None;
+        self.config.timeouts.request = Some(timeout);
+        self.config.timeouts.methods.clear();
+        self
+    }
+
+    /// Executes a GET request. Returns Response. Non-success HTTP statuses are returned
+    /// as errors.
     /// 
     /// # Arguments
     /// * `method` - API method
     /// * `params` - List of (name, value) tuples to use as query parameters
     pub async fn get(&self, method: &str, params: &[(&str, &str)]) -> APIResponse {
-        // Generate url
-        let url = format!("{url}/{method}", url=self.url, method=method);
+        API::check(method, self.get_raw(method, params).await?).await
+    }
+
+    /// Executes a POST request. Returns Response. Non-success HTTP statuses are returned
+    /// as errors.
+    /// 
+    /// # Arguments
+    /// * `method` - API method
+    /// * `json` - Value object with post body 
+    pub async fn post(&self, method: &str, json: &Value) -> APIResponse {
+        API::check(method, self.post_raw(method, json).await?).await
+    }
+
+    /// Executes a POST request with query parameters. Returns Response. Non-success HTTP
+    /// statuses are returned as errors.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `params` - List of (name, value) tuples to use as query parameters
+    /// * `json` - Value object with post body
+    pub async fn post_query(&self, method: &str, params: &[(&str, &str)], json: &Value) -> APIResponse {
+        // Create request
+        let mut request = self.client.post(self.endpoint(method));
+
+        // Set headers
+        request = self.headers(request);
+
+        // Execute API call
+        API::check(method, self.execute(method, request.query(&params).json(&json)).await?).await
+    }
 
-        // Create client
-        let client = reqwest::Client::new();
-        let mut request = client.get(&url);
+    /// Executes a multipart POST request. Returns Response. Non-success HTTP statuses are
+    /// returned as errors.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `form` - Multipart form data
+    pub async fn post_multipart(&self, method: &str, form: multipart::Form) -> APIResponse {
+        API::check(method, self.post_multipart_raw(method, form).await?).await
+    }
+
+    /// Executes a GET request. Returns Response without checking the HTTP status.
+    /// 
+    /// # Arguments
+    /// * `method` - API method
+    /// * `params` - List of (name, value) tuples to use as query parameters
+    pub async fn get_raw(&self, method: &str, params: &[(&str, &str)]) -> APIResponse {
+        // Create request
+        let mut request = self.client.get(self.endpoint(method));
 
         // Set headers
         request = self.headers(request);
 
         // Execute API call
-        Ok(request.query(&params).send().await?)
+        self.execute(method, request.query(&params)).await
     }
 
-    /// Executes a POST request. Returns Response.
+    /// Executes a POST request. Returns Response without checking the HTTP status.
     /// 
     /// # Arguments
     /// * `method` - API method
     /// * `json` - Value object with post body 
-    pub async fn post(&self, method: &str, json: &Value) -> APIResponse {
-        // Generate url
-        let url = format!("{url}/{method}", url=self.url, method=method);
+    pub async fn post_raw(&self, method: &str, json: &Value) -> APIResponse {
+        // Create request
+        let mut request = self.client.post(self.endpoint(method));
+
+        // Set headers
+        request = self.headers(request);
 
-        // Create client
-        let client = reqwest::Client::new();
-        let mut request = client.post(&url);
+        // Execute API call
+        self.execute(method, request.json(&json)).await
+    }
+
+    /// Executes a multipart POST request. Returns Response without checking the HTTP status.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `form` - Multipart form data
+    pub async fn post_multipart_raw(&self, method: &str, form: multipart::Form) -> APIResponse {
+        // Create request
+        let mut request = self.client.post(self.endpoint(method));
 
         // Set headers
         request = self.headers(request);
 
         // Execute API call
-        Ok(request.json(&json).send().await?)
+        self.execute(method, request.multipart(form)).await
+    }
+
+    /// Executes a GET request and decodes the JSON response.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `params` - List of (name, value) tuples to use as query parameters
+    pub async fn get_json<T: DeserializeOwned>(&self, method: &str, params: &[(&str, &str)]) -> Result<T> {
+        API::decode(self.get(method, params).await?).await
+    }
+
+    /// Executes a POST request and decodes the JSON response.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `json` - Value object with post body
+    pub async fn post_json<T: DeserializeOwned>(&self, method: &str, json: &Value) -> Result<T> {
+        API::decode(self.post(method, json).await?).await
+    }
+
+    /// Sends a request, retrying failures allowed by the retry policy. Requests with
+    /// streaming bodies, such as multipart uploads, can't be replayed and are sent once.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `request` - request to send
+    async fn execute(&self, method: &str, request: RequestBuilder) -> APIResponse {
+        // Apply request timeout
+        let request = match self.config.timeouts.request(method) {
+            Some(timeout) => request.timeout(timeout),
+            None => request
+        };
+
+        let mut attempt = 1;
+
+        loop {
+            // Keep a copy of the request when another attempt is allowed
+            let current = match request.try_clone() {
+                Some(current) if attempt < self.config.retry.max_attempts => current,
+                _ => return self.send(method, request).await
+            };
+
+            let delay = match self.send(method, current).await {
+                Ok(response) if self.config.retry.retry_response(method, &response) => self.config.retry.delay(attempt, Some(&response)),
+                Ok(response) => return Ok(response),
+                Err(error) if self.config.retry.retry_error(method, &error) => self.config.retry.delay(attempt, None),
+                Err(error) => return Err(error)
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends a single request, applying the read timeout while waiting for the response.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `request` - request to send
+    async fn send(&self, method: &str, request: RequestBuilder) -> APIResponse {
+        match self.config.timeouts.read {
+            Some(timeout) => match tokio::time::timeout(timeout, request.send()).await {
+                Ok(response) => Ok(response?),
+                Err(_) => Err(TxtaiError::Timeout { endpoint: method.to_string(), source: None })
+            },
+            None => Ok(request.send().await?)
+        }
+    }
+
+    /// Checks the HTTP status of a response. Returns the response when successful, otherwise
+    /// an error with the status code, API method and txtai error detail.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `response` - API response
+    pub async fn check(method: &str, response: Response) -> APIResponse {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response)
+        }
+
+        // Read error detail, FastAPI returns errors as {"detail": value}
+        let body = response.text().await.unwrap_or_default();
+        let detail = match serde_json::from_str::<Value>(&body) {
+            Ok(Value::Object(mut fields)) if fields.contains_key("detail") => {
+                match fields.remove("detail") {
+                    Some(Value::String(text)) => text,
+                    Some(value) => value.to_string(),
+                    None => body
+                }
+            },
+            _ => body
+        };
+
+        let (endpoint, status) = (method.to_string(), status.as_u16());
+        if status == 401 || status == 403 {
+            Err(TxtaiError::Auth { endpoint, status, detail })
+        }
+        else {
+            Err(TxtaiError::Status { endpoint, status, detail })
+        }
+    }
+
+    /// Decodes a JSON response body. The raw body is returned with the error when
+    /// it doesn't match the expected type.
+    ///
+    /// # Arguments
+    /// * `response` - API response
+    pub async fn decode<T: DeserializeOwned>(response: Response) -> Result<T> {
+        let body = response.text().await?;
+        serde_json::from_str(&body).map_err(|source| TxtaiError::Decode { source, body })
     }
 
     /// Sets headers on a request.
@@ -100,30 +341,135 @@ impl API {
     //  * `request` - RequestBuilder
     pub fn headers(&self, request: RequestBuilder) -> RequestBuilder {
         // Authorization header
-        if self.token != "" {
-            return request.header("Authorization", format!("Bearer {token}", token=self.token))
+        if !self.config.token.is_empty() {
+            return request.header("Authorization", format!("Bearer {token}", token=self.config.token))
         }
-        return request
+        request
     }
 
-    /// Executes a multipart POST request. Returns Response.
+    /// Generates the url for an API method.
     ///
     /// # Arguments
     /// * `method` - API method
-    /// * `form` - Multipart form data
-    pub async fn post_multipart(&self, method: &str, form: multipart::Form) -> APIResponse {
-        // Generate url
-        let url = format!("{url}/{method}", url=self.url, method=method);
+    fn endpoint(&self, method: &str) -> String {
+        format!("{url}/{method}", url=self.config.url, method=method)
+    }
 
-        // Create client
-        let client = reqwest::Client::new();
-        let mut request = client.post(&url);
+    /// Gets the process-wide client used by API instances created without custom
+    /// pool settings. All of these instances share a single connection pool.
+    fn shared() -> Client {
+        static CLIENT: OnceLock<Client> = OnceLock::new();
+        CLIENT.get_or_init(|| ClientBuilder::new().client().expect("failed to initialize HTTP client")).clone()
+    }
+}
 
-        // Set headers
-        request = self.headers(request);
+impl Default for API {
+    fn default() -> Self {
+        API::new()
+    }
+}
 
-        // Execute API call
-        Ok(request.multipart(form).send().await?)
+/// Connection pool settings
+#[derive(Clone, Debug)]
+pub struct Pool {
+    /// Maximum number of idle connections kept per host
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept before it is closed, None keeps connections indefinitely
+    pub idle_timeout: Option<Duration>,
+    /// TCP keep-alive interval, None disables TCP keep-alive
+    pub tcp_keepalive: Option<Duration>,
+    /// Only use HTTP/2 (prior knowledge), requires a server that accepts HTTP/2 without upgrade
+    pub http2: bool,
+    /// Interval for HTTP/2 keep-alive pings, None disables pings
+    pub http2_keepalive: Option<Duration>
+}
+
+/// Pool implementation
+impl Pool {
+    /// Builds a client backed by a connection pool with these settings. Returns an error if
+    /// the HTTP client can't be initialized.
+    pub fn client(&self) -> Result<Client> {
+        Ok(self.apply(Client::builder()).build()?)
+    }
+
+    /// Applies pool settings to a client builder.
+    ///
+    /// # Arguments
+    /// * `builder` - client builder
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        let mut builder = builder
+            .pool_max_idle_per_host(self.max_idle_per_host)
+            .pool_idle_timeout(self.idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive);
+
+        if self.http2 {
+            builder = builder.http2_prior_knowledge();
+        }
+
+        if let Some(interval) = self.http2_keepalive {
+            builder = builder
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_while_idle(true);
+        }
+
+        builder
+    }
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Pool {
+            max_idle_per_host: 32,
+            idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            http2: false,
+            http2_keepalive: None
+        }
+    }
+}
+
+/// Timeout settings. Timeouts that aren't set are unlimited.
+#[derive(Clone, Debug, Default)]
+pub struct Timeouts {
+    /// Time allowed to establish a connection
+    pub connect: Option<Duration>,
+    /// Time allowed waiting for a response once a request is sent
+    pub read: Option<Duration>,
+    /// Time allowed for a full request, from connecting until the response body is read
+    pub request: Option<Duration>,
+    /// Request timeouts for specific API methods, overrides the request timeout
+    pub methods: HashMap<String, Duration>
+}
+
+/// Timeouts implementation
+impl Timeouts {
+    /// Sets the request timeout for an API method.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `timeout` - request timeout
+    pub fn method(mut self, method: &str, timeout: Duration) -> Timeouts {
+        self.methods.insert(method.to_string(), timeout);
+        self
+    }
+
+    /// Gets the request timeout for an API method.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    pub fn request(&self, method: &str) -> Option<Duration> {
+        self.methods.get(method).copied().or(self.request)
+    }
+
+    /// Applies client-level timeout settings to a client builder.
+    ///
+    /// # Arguments
+    /// * `builder` - client builder
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match self.connect {
+            Some(timeout) => builder.connect_timeout(timeout),
+            None => builder
+        }
     }
 }
 
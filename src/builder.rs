@@ -0,0 +1,207 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{tls, Certificate, Client, Proxy};
+use std::env;
+use std::sync::Arc;
+
+use crate::api::{Pool, Timeouts, API};
+use crate::error::{Result, TxtaiError};
+use crate::retry::RetryPolicy;
+
+/// Default user agent sent with every request
+const USER_AGENT: &str = concat!("txtai.rs/", env!("CARGO_PKG_VERSION"));
+
+/// Builds API instances with custom client settings
+#[derive(Clone, Debug)]
+pub struct ClientBuilder {
+    pub(crate) url: String,
+    pub(crate) token: String,
+    headers: Vec<(String, String)>,
+    user_agent: String,
+    proxy: Option<String>,
+    certificates: Vec<Vec<u8>>,
+    accept_invalid_certs: bool,
+    min_tls_version: Option<tls::Version>,
+    pub(crate) pool: Pool,
+    pub(crate) timeouts: Timeouts,
+    pub(crate) retry: RetryPolicy
+}
+
+/// ClientBuilder implementation
+impl ClientBuilder {
+    /// Creates a ClientBuilder. The url and token default to the TXTAI_API_URL and
+    /// TXTAI_API_TOKEN environment variables.
+    pub fn new() -> ClientBuilder {
+        ClientBuilder {
+            url: env::var("TXTAI_API_URL").unwrap_or_default(),
+            token: env::var("TXTAI_API_TOKEN").unwrap_or_default(),
+            headers: Vec::new(),
+            user_agent: USER_AGENT.to_string(),
+            proxy: None,
+            certificates: Vec::new(),
+            accept_invalid_certs: false,
+            min_tls_version: None,
+            pool: Pool::default(),
+            timeouts: Timeouts::default(),
+            retry: RetryPolicy::default()
+        }
+    }
+
+    /// Sets the API url.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    pub fn url(mut self, url: &str) -> ClientBuilder {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Sets the API token.
+    ///
+    /// # Arguments
+    /// * `token` - API token
+    pub fn token(mut self, token: &str) -> ClientBuilder {
+        self.token = token.to_string();
+        self
+    }
+
+    /// Adds a header sent with every request.
+    ///
+    /// # Arguments
+    /// * `name` - header name
+    /// * `value` - header value
+    pub fn header(mut self, name: &str, value: &str) -> ClientBuilder {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the user agent.
+    ///
+    /// # Arguments
+    /// * `user_agent` - user agent
+    pub fn user_agent(mut self, user_agent: &str) -> ClientBuilder {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Routes all requests through a proxy.
+    ///
+    /// # Arguments
+    /// * `url` - proxy url
+    pub fn proxy(mut self, url: &str) -> ClientBuilder {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    /// Adds a trusted root certificate.
+    ///
+    /// # Arguments
+    /// * `pem` - PEM encoded certificate
+    pub fn certificate(mut self, pem: &[u8]) -> ClientBuilder {
+        self.certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Disables TLS certificate validation. Only use this for testing against servers
+    /// with self-signed certificates.
+    ///
+    /// # Arguments
+    /// * `accept` - accept invalid certificates if true
+    pub fn accept_invalid_certs(mut self, accept: bool) -> ClientBuilder {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Sets the minimum TLS version.
+    ///
+    /// # Arguments
+    /// * `version` - minimum TLS version
+    pub fn min_tls_version(mut self, version: tls::Version) -> ClientBuilder {
+        self.min_tls_version = Some(version);
+        self
+    }
+
+    /// Sets connection pool settings.
+    ///
+    /// # Arguments
+    /// * `pool` - connection pool settings
+    pub fn pool(mut self, pool: Pool) -> ClientBuilder {
+        self.pool = pool;
+        self
+    }
+
+    /// Sets timeout settings.
+    ///
+    /// # Arguments
+    /// * `timeouts` - timeout settings
+    pub fn timeouts(mut self, timeouts: Timeouts) -> ClientBuilder {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets the retry policy.
+    ///
+    /// # Arguments
+    /// * `retry` - retry policy
+    pub fn retry(mut self, retry: RetryPolicy) -> ClientBuilder {
+        self.retry = retry;
+        self
+    }
+
+    /// Builds an API instance.
+    pub fn build(self) -> Result<API> {
+        let client = self.client()?;
+        Ok(API::from_builder(self, client))
+    }
+
+    /// Builds an API instance wrapped in an Arc, ready to be shared across pipelines.
+    pub fn build_shared(self) -> Result<Arc<API>> {
+        self.build().map(Arc::new)
+    }
+
+    /// Builds a HTTP client using the connection, header, proxy and TLS settings.
+    pub(crate) fn client(&self) -> Result<Client> {
+        // Default headers
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| TxtaiError::InvalidInput(format!("header name {}: {}", name, e)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| TxtaiError::InvalidInput(format!("header value for {}: {}", name, e)))?;
+            headers.append(name, value);
+        }
+
+        let mut builder = Client::builder()
+            .default_headers(headers)
+            .user_agent(self.user_agent.as_str())
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+
+        // Proxy
+        if let Some(url) = &self.proxy {
+            let proxy = Proxy::all(url.as_str())
+                .map_err(|e| TxtaiError::InvalidInput(format!("proxy {}: {}", url, e)))?;
+            builder = builder.proxy(proxy);
+        }
+
+        // TLS
+        for pem in &self.certificates {
+            let certificate = Certificate::from_pem(pem)
+                .map_err(|e| TxtaiError::InvalidInput(format!("certificate: {}", e)))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        if let Some(version) = self.min_tls_version {
+            builder = builder.min_tls_version(version);
+        }
+
+        // Connection pool and timeouts
+        builder = self.timeouts.apply(self.pool.apply(builder));
+
+        Ok(builder.build()?)
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        ClientBuilder::new()
+    }
+}
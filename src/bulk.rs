@@ -0,0 +1,218 @@
+use futures::future;
+use futures::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use std::fmt;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+
+use crate::embeddings::Embeddings;
+use crate::error::{Result, TxtaiError};
+
+/// Progress callback
+pub type ProgressFn = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Bulk definition. Uploads documents to an embeddings index in batches with bounded
+/// concurrency. Only the batches in flight are held in memory.
+#[derive(Clone)]
+pub struct Bulk {
+    embeddings: Embeddings,
+    batch: usize,
+    concurrency: usize,
+    progress: Option<ProgressFn>
+}
+
+/// Bulk implementation
+impl Bulk {
+    /// Creates a Bulk instance with a batch size of 1000 and 4 concurrent uploads.
+    ///
+    /// # Arguments
+    /// * `embeddings` - embeddings instance
+    pub fn new(embeddings: Embeddings) -> Bulk {
+        Bulk {
+            embeddings,
+            batch: 1000,
+            concurrency: 4,
+            progress: None
+        }
+    }
+
+    /// Sets the number of documents per batch.
+    ///
+    /// # Arguments
+    /// * `batch` - batch size
+    pub fn batch(mut self, batch: usize) -> Bulk {
+        self.batch = batch.max(1);
+        self
+    }
+
+    /// Sets the maximum number of batches uploaded concurrently.
+    ///
+    /// # Arguments
+    /// * `concurrency` - maximum concurrent uploads
+    pub fn concurrency(mut self, concurrency: usize) -> Bulk {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets a callback that is invoked as each batch completes or fails.
+    ///
+    /// # Arguments
+    /// * `progress` - progress callback
+    pub fn progress(mut self, progress: impl Fn(&Progress) + Send + Sync + 'static) -> Bulk {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Adds documents from an iterator.
+    ///
+    /// # Arguments
+    /// * `documents` - documents to add
+    pub async fn add<T: Serialize>(&self, documents: impl IntoIterator<Item = T>) -> Result<Report> {
+        self.add_stream(stream::iter(documents)).await
+    }
+
+    /// Adds documents from a stream.
+    ///
+    /// # Arguments
+    /// * `documents` - documents to add
+    pub async fn add_stream<T: Serialize>(&self, documents: impl Stream<Item = T>) -> Result<Report> {
+        self.try_add_stream(documents.map(Ok)).await
+    }
+
+    /// Adds documents from a stream of results, such as documents parsed from a file. Reading
+    /// stops at the first error. Documents read before the error are uploaded and the error
+    /// is returned in `Report::error`.
+    ///
+    /// # Arguments
+    /// * `documents` - documents to add
+    pub async fn try_add_stream<T: Serialize>(&self, documents: impl Stream<Item = Result<T>>) -> Result<Report> {
+        // End the input at the first read error
+        let error = Mutex::new(None);
+        let documents = documents.scan((), |_, document| future::ready(match document {
+            Ok(document) => Some(document),
+            Err(e) => {
+                *error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e);
+                None
+            }
+        }));
+
+        let uploads = documents.chunks(self.batch).enumerate().map(|(batch, documents)| async move {
+            let start = batch * self.batch;
+            let result = self.embeddings.add(&documents).await.map(|_| ());
+            (batch, start, documents.len(), result)
+        }).buffer_unordered(self.concurrency);
+        let mut uploads = pin!(uploads);
+
+        let mut report = Report::default();
+
+        while let Some((batch, start, size, result)) = uploads.next().await {
+            report.batches += 1;
+            if result.is_ok() {
+                report.documents += size;
+            }
+
+            if let Some(progress) = &self.progress {
+                progress(&Progress { batch, start, size, uploaded: report.documents, error: result.as_ref().err() });
+            }
+
+            if let Err(error) = result {
+                report.failures.push(BatchFailure { batch, start, size, error });
+            }
+        }
+
+        report.error = error.lock().unwrap_or_else(|e| e.into_inner()).take();
+        Ok(report)
+    }
+
+    /// Adds documents from a stream and builds a new index. The index is only built when
+    /// all batches uploaded successfully.
+    ///
+    /// # Arguments
+    /// * `documents` - documents to add
+    pub async fn index<T: Serialize>(&self, documents: impl Stream<Item = Result<T>>) -> Result<Report> {
+        let mut report = self.try_add_stream(documents).await?;
+        if report.is_success() {
+            self.embeddings.index().await?;
+            report.indexed = true;
+        }
+
+        Ok(report)
+    }
+
+    /// Adds documents from a stream and upserts them into the index. The upsert only runs
+    /// when all batches uploaded successfully.
+    ///
+    /// # Arguments
+    /// * `documents` - documents to add
+    pub async fn upsert<T: Serialize>(&self, documents: impl Stream<Item = Result<T>>) -> Result<Report> {
+        let mut report = self.try_add_stream(documents).await?;
+        if report.is_success() {
+            self.embeddings.upsert().await?;
+            report.indexed = true;
+        }
+
+        Ok(report)
+    }
+}
+
+/// Progress of a completed batch
+#[derive(Debug)]
+pub struct Progress<'a> {
+    /// Batch number, starting at 0
+    pub batch: usize,
+    /// Position of the first document in this batch
+    pub start: usize,
+    /// Number of documents in this batch
+    pub size: usize,
+    /// Total documents uploaded so far
+    pub uploaded: usize,
+    /// Upload error, if this batch failed
+    pub error: Option<&'a TxtaiError>
+}
+
+/// Batch that failed to upload
+#[derive(Debug)]
+pub struct BatchFailure {
+    /// Batch number, starting at 0
+    pub batch: usize,
+    /// Position of the first document in this batch
+    pub start: usize,
+    /// Number of documents in this batch
+    pub size: usize,
+    /// Upload error
+    pub error: TxtaiError
+}
+
+/// Bulk upload report
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Number of batches processed
+    pub batches: usize,
+    /// Number of documents uploaded
+    pub documents: usize,
+    /// Batches that failed to upload
+    pub failures: Vec<BatchFailure>,
+    /// Error that stopped reading the input, documents after it weren't uploaded
+    pub error: Option<TxtaiError>,
+    /// True if the index or upsert operation ran
+    pub indexed: bool
+}
+
+/// Report implementation
+impl Report {
+    /// Returns true if the input was fully read and all batches uploaded successfully.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty() && self.error.is_none()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} documents uploaded in {} batches, {} failed", self.documents, self.batches, self.failures.len())?;
+        if let Some(error) = &self.error {
+            write!(f, ", input stopped: {}", error)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+use futures::future::{self, BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::Result;
+
+/// Cache key. Embeddings depend on the text, the index that generated them and the
+/// instruction category.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct CacheKey {
+    pub text: String,
+    pub index: Option<String>,
+    pub category: Option<String>
+}
+
+/// CacheKey implementation
+impl CacheKey {
+    /// Creates a CacheKey.
+    ///
+    /// # Arguments
+    /// * `text` - input text
+    /// * `category` - category, if applicable
+    /// * `index` - index name, if applicable
+    pub fn new(text: &str, category: Option<&str>, index: Option<&str>) -> CacheKey {
+        CacheKey {
+            text: text.to_string(),
+            index: index.map(|x| x.to_string()),
+            category: category.map(|x| x.to_string())
+        }
+    }
+
+    /// Stable 64-bit FNV-1a hash of this key. Unlike the std hasher, the value is the same
+    /// across processes and is used to name cache files.
+    fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let parts = [Some(self.text.as_str()), self.index.as_deref(), self.category.as_deref()];
+
+        for part in parts {
+            // Tag each part so None and Some("") hash differently
+            let bytes = part.map(|x| x.as_bytes());
+            for byte in [bytes.is_some() as u8].iter().chain(bytes.unwrap_or_default()).chain(&[0xff]) {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        hash
+    }
+}
+
+/// Cache hit and miss statistics
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64
+}
+
+/// CacheStats implementation
+impl CacheStats {
+    /// Fraction of lookups that were hits.
+    pub fn ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total > 0 { self.hits as f64 / total as f64 } else { 0.0 }
+    }
+}
+
+/// Embeddings cache. Implementations must be safe to share across tasks. Lookups and
+/// stores return futures so implementations backed by disk or network storage don't block
+/// the async runtime.
+pub trait Cache: Send + Sync {
+    /// Gets a cached embedding.
+    fn get<'a>(&'a self, key: &'a CacheKey) -> BoxFuture<'a, Option<Vec<f32>>>;
+
+    /// Stores an embedding.
+    fn put<'a>(&'a self, key: &'a CacheKey, embedding: &'a [f32]) -> BoxFuture<'a, ()>;
+
+    /// Gets hit and miss statistics.
+    fn stats(&self) -> CacheStats;
+}
+
+/// Thread-safe hit and miss counters
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64
+}
+
+/// Counters implementation
+impl Counters {
+    /// Records a lookup and passes the result through.
+    fn record<T>(&self, result: Option<T>) -> Option<T> {
+        let counter = if result.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed)
+        }
+    }
+}
+
+/// In-memory least recently used cache with optional expiration
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    ttl: Option<Duration>,
+    state: Mutex<MemoryState>,
+    counters: Counters
+}
+
+/// Memory cache entries and recency order
+#[derive(Debug, Default)]
+struct MemoryState {
+    entries: HashMap<CacheKey, MemoryEntry>,
+    order: BTreeMap<u64, CacheKey>,
+    tick: u64
+}
+
+/// Memory cache entry
+#[derive(Debug)]
+struct MemoryEntry {
+    embedding: Vec<f32>,
+    created: Instant,
+    tick: u64
+}
+
+/// MemoryCache implementation
+impl MemoryCache {
+    /// Creates a MemoryCache.
+    ///
+    /// # Arguments
+    /// * `capacity` - maximum number of entries, least recently used entries are evicted first
+    /// * `ttl` - time an entry stays valid, None keeps entries until evicted
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> MemoryCache {
+        MemoryCache {
+            capacity: capacity.max(1),
+            ttl,
+            state: Mutex::new(MemoryState::default()),
+            counters: Counters::default()
+        }
+    }
+
+    /// Number of cached entries.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).entries.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets a cached embedding and marks it as most recently used.
+    fn lookup(&self, key: &CacheKey) -> Option<Vec<f32>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = &mut *state;
+
+        let expired = match state.entries.get(key) {
+            Some(entry) => self.ttl.is_some_and(|ttl| entry.created.elapsed() > ttl),
+            None => return self.counters.record(None)
+        };
+
+        if expired {
+            if let Some(entry) = state.entries.remove(key) {
+                state.order.remove(&entry.tick);
+            }
+            return self.counters.record(None)
+        }
+
+        // Mark as most recently used
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(key)?;
+        state.order.remove(&entry.tick);
+        state.order.insert(tick, key.clone());
+        entry.tick = tick;
+
+        self.counters.record(Some(entry.embedding.clone()))
+    }
+
+    /// Stores an embedding, evicting least recently used entries over capacity.
+    fn store(&self, key: &CacheKey, embedding: &[f32]) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        state.tick += 1;
+        let tick = state.tick;
+
+        let entry = MemoryEntry { embedding: embedding.to_vec(), created: Instant::now(), tick };
+        if let Some(previous) = state.entries.insert(key.clone(), entry) {
+            state.order.remove(&previous.tick);
+        }
+        state.order.insert(tick, key.clone());
+
+        // Evict least recently used entries
+        while state.entries.len() > self.capacity {
+            match state.order.pop_first() {
+                Some((_, oldest)) => state.entries.remove(&oldest),
+                None => break
+            };
+        }
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get<'a>(&'a self, key: &'a CacheKey) -> BoxFuture<'a, Option<Vec<f32>>> {
+        future::ready(self.lookup(key)).boxed()
+    }
+
+    fn put<'a>(&'a self, key: &'a CacheKey, embedding: &'a [f32]) -> BoxFuture<'a, ()> {
+        self.store(key, embedding);
+        future::ready(()).boxed()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.counters.stats()
+    }
+}
+
+/// Persistent cache that stores each embedding as a file in a directory
+#[derive(Debug)]
+pub struct DiskCache {
+    directory: PathBuf,
+    ttl: Option<Duration>,
+    counters: Counters
+}
+
+/// Disk cache file contents
+#[derive(Deserialize, Serialize)]
+struct DiskEntry {
+    text: String,
+    index: Option<String>,
+    category: Option<String>,
+    created: u64,
+    embedding: Vec<f32>
+}
+
+/// DiskCache implementation
+impl DiskCache {
+    /// Creates a DiskCache, creating the directory if necessary. The directory is created
+    /// with blocking IO, create caches during setup rather than on a hot path.
+    ///
+    /// # Arguments
+    /// * `directory` - cache directory
+    /// * `ttl` - time an entry stays valid, None keeps entries indefinitely
+    pub fn new(directory: impl AsRef<Path>, ttl: Option<Duration>) -> Result<DiskCache> {
+        fs::create_dir_all(&directory)?;
+
+        Ok(DiskCache {
+            directory: directory.as_ref().to_path_buf(),
+            ttl,
+            counters: Counters::default()
+        })
+    }
+
+    /// Removes all cached entries.
+    pub async fn clear(&self) -> Result<()> {
+        let mut entries = tokio::fs::read_dir(&self.directory).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|x| x == "json") {
+                tokio::fs::remove_file(path).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the file path for a key.
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.directory.join(format!("{:016x}.json", key.fingerprint()))
+    }
+
+    /// Reads a cache entry. Missing, unreadable, expired and colliding entries are misses.
+    async fn read(&self, key: &CacheKey) -> Option<Vec<f32>> {
+        let data = tokio::fs::read(self.path(key)).await.ok()?;
+        let entry: DiskEntry = serde_json::from_slice(&data).ok()?;
+
+        let matches = entry.text == key.text && entry.index == key.index && entry.category == key.category;
+        let expired = self.ttl.is_some_and(|ttl| now().saturating_sub(entry.created) > ttl.as_secs());
+
+        (matches && !expired).then_some(entry.embedding)
+    }
+
+    /// Writes a cache entry. Entries are written to a unique temporary file and renamed, so
+    /// readers never see partial entries.
+    async fn write(&self, key: &CacheKey, embedding: &[f32]) {
+        let entry = DiskEntry {
+            text: key.text.clone(),
+            index: key.index.clone(),
+            category: key.category.clone(),
+            created: now(),
+            embedding: embedding.to_vec()
+        };
+
+        // Caching is best effort, write failures only cost a future cache miss
+        if let Ok(data) = serde_json::to_vec(&entry) {
+            let path = self.path(key);
+            let temp = path.with_extension(format!("{}.tmp", WRITES.fetch_add(1, Ordering::Relaxed)));
+            if tokio::fs::write(&temp, data).await.is_ok() && tokio::fs::rename(&temp, &path).await.is_err() {
+                let _ = tokio::fs::remove_file(&temp).await;
+            }
+        }
+    }
+}
+
+impl Cache for DiskCache {
+    fn get<'a>(&'a self, key: &'a CacheKey) -> BoxFuture<'a, Option<Vec<f32>>> {
+        async move { self.counters.record(self.read(key).await) }.boxed()
+    }
+
+    fn put<'a>(&'a self, key: &'a CacheKey, embedding: &'a [f32]) -> BoxFuture<'a, ()> {
+        self.write(key, embedding).boxed()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.counters.stats()
+    }
+}
+
+/// Counter used to name temporary files for concurrent writes
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// Current time in seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
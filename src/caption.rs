@@ -0,0 +1,155 @@
+use reqwest::multipart;
+use serde_json::json;
+use std::sync::Arc;
+
+pub use crate::api::{API, Result, Strings, StringsBatch, TxtaiError};
+
+/// Caption definition
+#[derive(Clone)]
+pub struct Caption {
+    api: Arc<API>
+}
+
+/// Caption implementation
+impl Caption {
+    /// Creates a Caption instance.
+    ///
+    pub fn new() -> Caption {
+        Caption {
+            api: Arc::new(API::new())
+        }
+    }
+
+    /// Creates a Caption instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    pub fn with_url(url: &str) -> Caption {
+        Caption {
+            api: Arc::new(API::with_url(url))
+        }
+    }
+
+    /// Creates a Caption instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    /// * `token` - API token
+    pub fn with_url_token(url: &str, token: &str) -> Caption {
+        Caption {
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Caption instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Caption {
+        Caption {
+            api: api.into()
+        }
+    }
+
+    /// Builds a caption for an image.
+    ///
+    /// # Arguments
+    /// * `file` - image file path or url accessible to the server
+    pub async fn caption(&self, file: &str) -> Strings {
+        // Query parameters
+        let params = [("file", file)];
+
+        // Execute API call
+        self.api.get_json("caption", &params).await
+    }
+
+    /// Builds captions for a list of images.
+    ///
+    /// # Arguments
+    /// * `files` - list of image file paths or urls accessible to the server
+    pub async fn batchcaption(&self, files: &[&str]) -> StringsBatch {
+        // Post parameters
+        let params = json!(files);
+
+        // Execute API call
+        self.api.post_json("batchcaption", &params).await
+    }
+
+    /// Uploads an image and builds a caption for it.
+    ///
+    /// # Arguments
+    /// * `data` - image bytes
+    pub async fn caption_bytes(&self, data: Vec<u8>) -> Strings {
+        let files = self.upload(vec![data]).await?;
+        match files.first() {
+            Some(file) => self.caption(file).await,
+            None => Err(TxtaiError::Response { endpoint: "upload".to_string(), detail: "no files returned".to_string() })
+        }
+    }
+
+    /// Uploads a list of images and builds captions for them.
+    ///
+    /// # Arguments
+    /// * `data` - list of image bytes
+    pub async fn batchcaption_bytes(&self, data: Vec<Vec<u8>>) -> StringsBatch {
+        let count = data.len();
+        let files = self.upload(data).await?;
+
+        if files.len() != count {
+            return Err(TxtaiError::Response {
+                endpoint: "upload".to_string(),
+                detail: format!("expected {} uploaded files, found {}", count, files.len())
+            });
+        }
+
+        self.batchcaption(&files.iter().map(|x| x.as_str()).collect::<Vec<_>>()).await
+    }
+
+    /// Uploads images to the server. Returns the server file paths in input order.
+    ///
+    /// # Arguments
+    /// * `data` - list of image bytes
+    pub async fn upload(&self, data: Vec<Vec<u8>>) -> Result<Vec<String>> {
+        let mut form = multipart::Form::new();
+
+        // Add image data
+        for (i, bytes) in data.into_iter().enumerate() {
+            let (extension, mime) = format(&bytes);
+            let part = multipart::Part::bytes(bytes)
+                .file_name(format!("image{}.{}", i, extension))
+                .mime_str(mime)?;
+            form = form.part("files", part);
+        }
+
+        // Execute API call
+        let response = self.api.post_multipart("upload", form).await?;
+        API::decode(response).await
+    }
+}
+
+impl Default for Caption {
+    fn default() -> Self {
+        Caption::new()
+    }
+}
+
+/// Detects the image format from its leading bytes. Returns the file extension and mime
+/// type, unknown formats are sent as JPEG.
+fn format(data: &[u8]) -> (&'static str, &'static str) {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        ("png", "image/png")
+    }
+    else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        ("gif", "image/gif")
+    }
+    else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        ("webp", "image/webp")
+    }
+    else if data.starts_with(b"BM") {
+        ("bmp", "image/bmp")
+    }
+    else {
+        ("jpg", "image/jpeg")
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use crate::api::API;
+use crate::caption::Caption;
+use crate::embeddings::Embeddings;
+use crate::extractor::Extractor;
+use crate::ingest::Ingest;
+use crate::labels::Labels;
+use crate::llm::LLM;
+use crate::rag::RAG;
+use crate::segmentation::Segmentation;
+use crate::similarity::Similarity;
+use crate::summary::Summary;
+use crate::textractor::Textractor;
+use crate::transcription::Transcription;
+use crate::translation::Translation;
+use crate::workflow::Workflow;
+
+/// Txtai definition. Single entry point holding one shared API connection. Pipelines
+/// created from a Txtai instance share its configuration, authentication and connection pool.
+#[derive(Clone)]
+pub struct Txtai {
+    api: Arc<API>
+}
+
+/// Txtai implementation
+impl Txtai {
+    /// Creates a Txtai instance.
+    ///
+    pub fn new() -> Txtai {
+        Txtai {
+            api: Arc::new(API::new())
+        }
+    }
+
+    /// Creates a Txtai instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    pub fn with_url(url: &str) -> Txtai {
+        Txtai {
+            api: Arc::new(API::with_url(url))
+        }
+    }
+
+    /// Creates a Txtai instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    /// * `token` - API token
+    pub fn with_url_token(url: &str, token: &str) -> Txtai {
+        Txtai {
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Txtai instance from an existing API or Arc<API>. Use this with
+    /// `API::builder()` to customize the connection.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Txtai {
+        Txtai {
+            api: api.into()
+        }
+    }
+
+    /// Gets the shared API instance.
+    pub fn api(&self) -> &Arc<API> {
+        &self.api
+    }
+
+    /// Creates a Caption instance using the shared API.
+    pub fn caption(&self) -> Caption {
+        Caption::with_api(self.api.clone())
+    }
+
+    /// Creates an Embeddings instance using the shared API.
+    pub fn embeddings(&self) -> Embeddings {
+        Embeddings::with_api(self.api.clone())
+    }
+
+    /// Creates an Extractor instance using the shared API.
+    pub fn extractor(&self) -> Extractor {
+        Extractor::with_api(self.api.clone())
+    }
+
+    /// Creates an Ingest instance using the shared API.
+    pub fn ingest(&self) -> Ingest {
+        Ingest::with_api(self.api.clone())
+    }
+
+    /// Creates a Labels instance using the shared API.
+    pub fn labels(&self) -> Labels {
+        Labels::with_api(self.api.clone())
+    }
+
+    /// Creates a LLM instance using the shared API.
+    pub fn llm(&self) -> LLM {
+        LLM::with_api(self.api.clone())
+    }
+
+    /// Creates a RAG instance using the shared API.
+    pub fn rag(&self) -> RAG {
+        RAG::with_api(self.api.clone())
+    }
+
+    /// Creates a Segmentation instance using the shared API.
+    pub fn segmentation(&self) -> Segmentation {
+        Segmentation::with_api(self.api.clone())
+    }
+
+    /// Creates a Similarity instance using the shared API.
+    pub fn similarity(&self) -> Similarity {
+        Similarity::with_api(self.api.clone())
+    }
+
+    /// Creates a Summary instance using the shared API.
+    pub fn summary(&self) -> Summary {
+        Summary::with_api(self.api.clone())
+    }
+
+    /// Creates a Textractor instance using the shared API.
+    pub fn textractor(&self) -> Textractor {
+        Textractor::with_api(self.api.clone())
+    }
+
+    /// Creates a Transcription instance using the shared API.
+    pub fn transcription(&self) -> Transcription {
+        Transcription::with_api(self.api.clone())
+    }
+
+    /// Creates a Translation instance using the shared API.
+    pub fn translation(&self) -> Translation {
+        Translation::with_api(self.api.clone())
+    }
+
+    /// Creates a Workflow instance using the shared API.
+    pub fn workflow(&self) -> Workflow {
+        Workflow::with_api(self.api.clone())
+    }
+}
+
+impl Default for Txtai {
+    fn default() -> Self {
+        Txtai::new()
+    }
+}
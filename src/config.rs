@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::error::{Result, TxtaiError};
+
+/// Embeddings configuration definition. Typed fields cover the common settings, any other
+/// setting is kept in `extra` and passed through unchanged.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Content>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hybrid: Option<Hybrid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<Scoring>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantize: Option<Quantize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph: Option<Graph>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexes: Option<BTreeMap<String, Config>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub functions: Option<Vec<Function>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>
+}
+
+/// Config implementation
+impl Config {
+    /// Creates an empty Config.
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Sets the vector model path.
+    ///
+    /// # Arguments
+    /// * `path` - model path or Hugging Face model id
+    pub fn path(mut self, path: &str) -> Config {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    /// Sets the vector method, for example "transformers" or "sentence-transformers".
+    ///
+    /// # Arguments
+    /// * `method` - vector method
+    pub fn method(mut self, method: &str) -> Config {
+        self.method = Some(method.to_string());
+        self
+    }
+
+    /// Sets the approximate nearest neighbor backend, for example "faiss" or "hnsw".
+    ///
+    /// # Arguments
+    /// * `backend` - ann backend
+    pub fn backend(mut self, backend: &str) -> Config {
+        self.backend = Some(backend.to_string());
+        self
+    }
+
+    /// Enables or disables content storage.
+    ///
+    /// # Arguments
+    /// * `content` - true to store content with the default database
+    pub fn content(mut self, content: bool) -> Config {
+        self.content = Some(Content::Enabled(content));
+        self
+    }
+
+    /// Enables content storage with a specific database, for example "duckdb" or a
+    /// database url.
+    ///
+    /// # Arguments
+    /// * `database` - content database
+    pub fn content_database(mut self, database: &str) -> Config {
+        self.content = Some(Content::Database(database.to_string()));
+        self
+    }
+
+    /// Enables or disables hybrid search, which combines sparse and dense scores.
+    ///
+    /// # Arguments
+    /// * `hybrid` - true to enable hybrid search
+    pub fn hybrid(mut self, hybrid: bool) -> Config {
+        self.hybrid = Some(Hybrid::Enabled(hybrid));
+        self
+    }
+
+    /// Sets the sparse scoring configuration.
+    ///
+    /// # Arguments
+    /// * `scoring` - scoring method name or ScoringConfig
+    pub fn scoring(mut self, scoring: impl Into<Scoring>) -> Config {
+        self.scoring = Some(scoring.into());
+        self
+    }
+
+    /// Enables scalar quantization with the given number of bits.
+    ///
+    /// # Arguments
+    /// * `bits` - number of bits per dimension
+    pub fn quantize(mut self, bits: u8) -> Config {
+        self.quantize = Some(Quantize::Bits(bits));
+        self
+    }
+
+    /// Sets the graph configuration.
+    ///
+    /// # Arguments
+    /// * `graph` - true for the default graph or a GraphConfig
+    pub fn graph(mut self, graph: impl Into<Graph>) -> Config {
+        self.graph = Some(graph.into());
+        self
+    }
+
+    /// Adds a subindex.
+    ///
+    /// # Arguments
+    /// * `name` - subindex name
+    /// * `config` - subindex configuration
+    pub fn index(mut self, name: &str, config: Config) -> Config {
+        self.indexes.get_or_insert_with(BTreeMap::new).insert(name.to_string(), config);
+        self
+    }
+
+    /// Adds a SQL function.
+    ///
+    /// # Arguments
+    /// * `function` - function reference or Function definition
+    pub fn function(mut self, function: impl Into<Function>) -> Config {
+        self.functions.get_or_insert_with(Vec::new).push(function.into());
+        self
+    }
+
+    /// Sets a setting that has no typed field.
+    ///
+    /// # Arguments
+    /// * `name` - setting name
+    /// * `value` - setting value
+    pub fn set(mut self, name: &str, value: impl Into<Value>) -> Config {
+        self.extra.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Validates this configuration. Checks that settings have usable values, that extra
+    /// settings don't shadow typed fields and that subindexes are valid and not nested.
+    pub fn validate(&self) -> Result<()> {
+        self.check(None)
+    }
+
+    /// Validates a configuration, `name` is set for subindexes.
+    fn check(&self, name: Option<&str>) -> Result<()> {
+        let context = name.map(|x| format!("indexes.{}: ", x)).unwrap_or_default();
+        let invalid = |message: String| Err(TxtaiError::InvalidInput(format!("{}{}", context, message)));
+
+        for (field, value) in [("path", &self.path), ("method", &self.method), ("backend", &self.backend)] {
+            if value.as_ref().is_some_and(|x| x.trim().is_empty()) {
+                return invalid(format!("{} must not be empty", field));
+            }
+        }
+
+        if let Some(Content::Database(database)) = &self.content {
+            if database.trim().is_empty() {
+                return invalid("content database must not be empty".to_string());
+            }
+        }
+
+        if let Some(Hybrid::Method(method)) = &self.hybrid {
+            if method.trim().is_empty() {
+                return invalid("hybrid method must not be empty".to_string());
+            }
+        }
+
+        let scoring = match &self.scoring {
+            Some(Scoring::Method(method)) => Some(method),
+            Some(Scoring::Config(config)) => Some(&config.method),
+            None => None
+        };
+        if scoring.is_some_and(|x| x.trim().is_empty()) {
+            return invalid("scoring method must not be empty".to_string());
+        }
+
+        if let Some(Quantize::Bits(bits)) = self.quantize {
+            if !(1..=8).contains(&bits) {
+                return invalid(format!("quantize bits must be between 1 and 8, found {}", bits));
+            }
+        }
+
+        if let Some(Graph::Config(graph)) = &self.graph {
+            if graph.minscore.is_some_and(|x| !(0.0..=1.0).contains(&x)) {
+                return invalid("graph minscore must be between 0 and 1".to_string());
+            }
+        }
+
+        for function in self.functions.iter().flatten() {
+            let reference = match function {
+                Function::Reference(reference) => reference,
+                Function::Definition { function, .. } => function
+            };
+
+            if reference.trim().is_empty() || function.name().is_some_and(|x| x.trim().is_empty()) {
+                return invalid("functions must have a non-empty name and function".to_string());
+            }
+        }
+
+        if let Some(field) = FIELDS.iter().find(|x| self.extra.contains_key(**x)) {
+            return invalid(format!("{} must be set with its typed field", field));
+        }
+
+        if let Some(indexes) = &self.indexes {
+            if name.is_some() {
+                return invalid("subindexes can't be nested".to_string());
+            }
+
+            for (name, config) in indexes {
+                if name.trim().is_empty() {
+                    return Err(TxtaiError::InvalidInput("subindex names must not be empty".to_string()));
+                }
+
+                config.check(Some(name))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Typed field names, these must not be used as extra settings
+const FIELDS: [&str; 10] = ["path", "method", "backend", "content", "hybrid", "scoring", "quantize", "graph", "indexes", "functions"];
+
+/// Content storage setting, either a flag or a database name or url
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Content {
+    Enabled(bool),
+    Database(String)
+}
+
+/// Hybrid search setting, either a flag or a sparse scoring method
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Hybrid {
+    Enabled(bool),
+    Method(String)
+}
+
+/// Scoring setting, either a method name or a full scoring configuration
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Scoring {
+    Method(String),
+    Config(ScoringConfig)
+}
+
+impl From<&str> for Scoring {
+    fn from(method: &str) -> Scoring {
+        Scoring::Method(method.to_string())
+    }
+}
+
+impl From<ScoringConfig> for Scoring {
+    fn from(config: ScoringConfig) -> Scoring {
+        Scoring::Config(config)
+    }
+}
+
+/// Sparse scoring configuration
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct ScoringConfig {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terms: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalize: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>
+}
+
+/// ScoringConfig implementation
+impl ScoringConfig {
+    /// Creates a ScoringConfig.
+    ///
+    /// # Arguments
+    /// * `method` - scoring method, for example "bm25", "tfidf" or "sif"
+    pub fn new(method: &str) -> ScoringConfig {
+        ScoringConfig { method: method.to_string(), ..Default::default() }
+    }
+
+    /// Enables or disables the term index.
+    ///
+    /// # Arguments
+    /// * `terms` - true to build a term index
+    pub fn terms(mut self, terms: bool) -> ScoringConfig {
+        self.terms = Some(terms);
+        self
+    }
+
+    /// Enables or disables score normalization.
+    ///
+    /// # Arguments
+    /// * `normalize` - true to normalize scores
+    pub fn normalize(mut self, normalize: bool) -> ScoringConfig {
+        self.normalize = Some(normalize);
+        self
+    }
+}
+
+/// Quantization setting, either a flag or a number of bits
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Quantize {
+    Enabled(bool),
+    Bits(u8)
+}
+
+/// Graph setting, either a flag or a full graph configuration
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Graph {
+    Enabled(bool),
+    Config(GraphConfig)
+}
+
+impl From<bool> for Graph {
+    fn from(enabled: bool) -> Graph {
+        Graph::Enabled(enabled)
+    }
+}
+
+impl From<GraphConfig> for Graph {
+    fn from(config: GraphConfig) -> Graph {
+        Graph::Config(config)
+    }
+}
+
+/// Graph configuration
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct GraphConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minscore: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approximate: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topics: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>
+}
+
+/// SQL function, either a function reference or a named definition
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Function {
+    Reference(String),
+    Definition {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        function: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        argcount: Option<i32>
+    }
+}
+
+/// Function implementation
+impl Function {
+    /// Creates a named function definition.
+    ///
+    /// # Arguments
+    /// * `name` - name used in SQL
+    /// * `function` - function reference
+    pub fn new(name: &str, function: &str) -> Function {
+        Function::Definition { name: Some(name.to_string()), function: function.to_string(), argcount: None }
+    }
+
+    /// Gets the function name, if set.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Function::Reference(_) => None,
+            Function::Definition { name, .. } => name.as_deref()
+        }
+    }
+}
+
+impl From<&str> for Function {
+    fn from(reference: &str) -> Function {
+        Function::Reference(reference.to_string())
+    }
+}
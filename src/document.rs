@@ -0,0 +1,120 @@
+use serde::ser::{self, Serialize, SerializeMap, Serializer};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::error::{Result, TxtaiError};
+
+/// Reserved document fields that can't be used as metadata names
+pub(crate) const RESERVED: [&str; 3] = ["id", "text", "object"];
+
+/// Input document. Metadata fields are sent alongside the text and become queryable
+/// columns when content storage is enabled. Serializing a document with a metadata field
+/// named id, text or object fails rather than overwriting the reserved field.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[non_exhaustive]
+pub struct Document {
+    pub id: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+    #[serde(flatten)]
+    pub metadata: Map<String, Value>
+}
+
+/// Document implementation
+impl Document {
+    /// Creates a Document.
+    ///
+    /// # Arguments
+    /// * `id` - document id
+    /// * `text` - document text
+    pub fn new(id: &str, text: &str) -> Document {
+        Document {
+            id: id.to_string(),
+            text: text.to_string(),
+            ..Document::default()
+        }
+    }
+
+    /// Sets a reference to the object indexed with this document, such as a file path or url.
+    ///
+    /// # Arguments
+    /// * `object` - object reference
+    pub fn object(mut self, object: &str) -> Document {
+        self.object = Some(object.to_string());
+        self
+    }
+
+    /// Adds a metadata field.
+    ///
+    /// # Arguments
+    /// * `name` - field name
+    /// * `value` - field value
+    pub fn with(mut self, name: &str, value: impl Into<Value>) -> Document {
+        self.metadata.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Checks that this document has an id, text or object and no metadata fields that
+    /// shadow the reserved id, text and object fields.
+    pub fn validate(&self) -> Result<()> {
+        let document = serde_json::to_value(self).map_err(|e| TxtaiError::InvalidInput(e.to_string()))?;
+        validate(&document)
+    }
+}
+
+impl Serialize for Document {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if let Some(name) = RESERVED.iter().find(|name| self.metadata.contains_key(**name)) {
+            return Err(ser::Error::custom(format!("document {}: metadata field {} is reserved", self.id, name)))
+        }
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("text", &self.text)?;
+        if let Some(object) = &self.object {
+            map.serialize_entry("object", object)?;
+        }
+        for (name, value) in &self.metadata {
+            map.serialize_entry(name, value)?;
+        }
+
+        map.end()
+    }
+}
+
+/// Checks that a serialized document is an object with an id, either text or an object and
+/// string values for the reserved text and object fields. This works for any type that serializes to a JSON object, including custom structs.
+///
+/// # Arguments
+/// * `document` - serialized document
+pub fn validate(document: &Value) -> Result<()> {
+    let fields = document.as_object()
+        .ok_or_else(|| TxtaiError::InvalidInput(format!("document must be an object: {}", document)))?;
+
+    // Ids can be strings or numbers
+    let id = match fields.get("id") {
+        Some(Value::String(id)) if !id.is_empty() => id.clone(),
+        Some(Value::Number(id)) => id.to_string(),
+        _ => return Err(TxtaiError::InvalidInput(format!("document is missing an id: {}", document)))
+    };
+
+    let present = |name| fields.get(name).is_some_and(|value| match value {
+        Value::Null => false,
+        Value::String(text) => !text.is_empty(),
+        _ => true
+    });
+
+    // Reserved fields must have the types txtai expects
+    for name in ["text", "object"] {
+        if fields.get(name).is_some_and(|value| !value.is_string() && !value.is_null()) {
+            return Err(TxtaiError::InvalidInput(format!("document {}: field {} must be a string", id, name)))
+        }
+    }
+
+    if !present("text") && !present("object") {
+        return Err(TxtaiError::InvalidInput(format!("document {} is missing text", id)))
+    }
+
+    Ok(())
+}
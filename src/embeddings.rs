@@ -1,14 +1,30 @@
+use futures::future;
 use reqwest::multipart;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
-use std::error::Error;
-
-pub use crate::api::{API, APIResponse, IndexResults, IndexResultsBatch};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub use crate::bulk::Bulk;
+pub use crate::cache::{Cache, CacheKey, CacheStats, DiskCache, MemoryCache};
+pub use crate::config::Config;
+pub use crate::api::{API, APIResponse, IndexResults, IndexResultsBatch, Result, TxtaiError};
+pub use crate::document::Document;
+pub use crate::graph::Graph;
+pub use crate::pages::Pages;
+pub use crate::query::{Query, DEFAULT_LIMIT};
+pub use crate::vectors::Vectors;
+
+/// Maximum number of texts sent per batchtransform request
+pub const TRANSFORM_BATCH: usize = 256;
 
 /// Embeddings definition
+#[derive(Clone)]
 pub struct Embeddings {
-    api: API
+    api: Arc<API>,
+    cache: Option<Arc<dyn Cache>>
 }
 
 /// Embeddings implementation
@@ -17,7 +33,8 @@ impl Embeddings {
     /// 
     pub fn new() -> Embeddings {
         Embeddings { 
-            api: API::new()
+            api: Arc::new(API::new()),
+            cache: None
         }
     }
 
@@ -27,7 +44,8 @@ impl Embeddings {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Embeddings {
         Embeddings {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)),
+            cache: None
         }
     }
 
@@ -38,12 +56,54 @@ impl Embeddings {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Embeddings {
         Embeddings {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token)),
+            cache: None
+        }
+    }
+
+    /// Creates an Embeddings instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Embeddings {
+        Embeddings {
+            api: api.into(),
+            cache: None
+        }
+    }
+
+    /// Creates a copy of this instance that applies `timeout` to every request. This is
+    /// used to override the configured timeouts for a call, for example a long running
+    /// `index` or `reindex`.
+    ///
+    /// # Arguments
+    /// * `timeout` - request timeout
+    pub fn timeout(&self, timeout: Duration) -> Embeddings {
+        Embeddings {
+            api: Arc::new((*self.api).clone().timeout(timeout)),
+            cache: self.cache.clone()
         }
     }
 
+    /// Sets a cache for `transform` and `batchtransform` results. Only texts missing from
+    /// the cache are sent to the API.
+    ///
+    /// # Arguments
+    /// * `cache` - embeddings cache, for example a MemoryCache or DiskCache
+    pub fn cache(mut self, cache: Arc<dyn Cache>) -> Embeddings {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Gets cache hit and miss statistics. Returns None when no cache is set.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
     /// Runs an Embeddings search. Returns Response. This method allows
-    /// callers to customize the serialization of the response.
+    /// callers to customize the serialization of the response. The HTTP status
+    /// is not checked.
     /// 
     /// # Arguments
     /// * `query` - query text
@@ -51,25 +111,11 @@ impl Embeddings {
     /// * `weights` - hybrid score weights, if applicable
     /// * `index` - index name, if applicable
     pub async fn query(&self, query: &str, limit: i32, weights: Option<f32>, index: Option<&str>) -> APIResponse {
-        // Query parameters
-        let mut params = vec![("query", query)];
-
-        let limitl = limit.to_string();
-        let weightsl = weights.unwrap_or(-1.0).to_string();
-        let indexl = index.unwrap_or("").to_string();
-
-        if limitl != "" {
-            params.push(("limit", &limitl));
-        }
-        if weightsl != "-1.0" {
-            params.push(("weights", &weightsl));
-        }
-        if indexl != "" {
-            params.push(("index", &indexl));
-        }
+        let params = Embeddings::params(query, limit, weights, index);
+        let params: Vec<(&str, &str)> = params.iter().map(|(name, value)| (*name, value.as_str())).collect();
 
         // Execute API call
-        Ok(self.api.get("search", &params).await?)
+        self.api.get_raw("search", &params).await
     }
 
     /// Finds documents in the embeddings model most similar to the input query. Returns
@@ -82,8 +128,38 @@ impl Embeddings {
     /// * `weights` - hybrid score weights, if applicable
     /// * `index` - index name, if applicable
     pub async fn search(&self, query: &str, limit: i32, weights: Option<f32>, index: Option<&str>) -> SearchResults {
+        self.search_as(query, limit, weights, index).await
+    }
+
+    /// Runs an Embeddings search and returns each result as a row of columns. This method
+    /// is used with SQL queries, which return the selected columns when content storage
+    /// is enabled.
+    ///
+    /// `select id, text, score, field from txtai where similar('query') and field = 'value'`
+    ///
+    /// # Arguments
+    /// * `query` - query text or SQL
+    /// * `limit` - maximum results
+    /// * `weights` - hybrid score weights, if applicable
+    /// * `index` - index name, if applicable
+    pub async fn search_rows(&self, query: &str, limit: i32, weights: Option<f32>, index: Option<&str>) -> Rows {
+        self.search_as(query, limit, weights, index).await
+    }
+
+    /// Runs an Embeddings search and maps each result into `T`. Field names of `T` are
+    /// matched against the returned columns.
+    ///
+    /// # Arguments
+    /// * `query` - query text or SQL
+    /// * `limit` - maximum results
+    /// * `weights` - hybrid score weights, if applicable
+    /// * `index` - index name, if applicable
+    pub async fn search_as<T: DeserializeOwned>(&self, query: &str, limit: i32, weights: Option<f32>, index: Option<&str>) -> Result<Vec<T>> {
+        let params = Embeddings::params(query, limit, weights, index);
+        let params: Vec<(&str, &str)> = params.iter().map(|(name, value)| (*name, value.as_str())).collect();
+
         // Execute API call and map JSON
-        Ok(self.query(query, limit, weights, index).await?.json().await?)
+        self.api.get_json("search", &params).await
     }
 
     /// Finds documents in the embeddings model most similar to the input queries. Returns
@@ -96,6 +172,29 @@ impl Embeddings {
     /// * `weights` - hybrid score weights, if applicable
     /// * `index` - index name, if applicable
     pub async fn batchsearch(&self, queries: &Vec<&str>, limit: i32, weights: Option<f32>, index: Option<&str>) -> SearchResultsBatch {
+        self.batchsearch_as(queries, limit, weights, index).await
+    }
+
+    /// Runs a batch of Embeddings searches and returns each result as a row of columns.
+    /// See `search_rows`.
+    ///
+    /// # Arguments
+    /// * `queries` - queries text or SQL
+    /// * `limit` - maximum results
+    /// * `weights` - hybrid score weights, if applicable
+    /// * `index` - index name, if applicable
+    pub async fn batchsearch_rows(&self, queries: &Vec<&str>, limit: i32, weights: Option<f32>, index: Option<&str>) -> RowsBatch {
+        self.batchsearch_as(queries, limit, weights, index).await
+    }
+
+    /// Runs a batch of Embeddings searches and maps each result into `T`. See `search_as`.
+    ///
+    /// # Arguments
+    /// * `queries` - queries text or SQL
+    /// * `limit` - maximum results
+    /// * `weights` - hybrid score weights, if applicable
+    /// * `index` - index name, if applicable
+    pub async fn batchsearch_as<T: DeserializeOwned>(&self, queries: &Vec<&str>, limit: i32, weights: Option<f32>, index: Option<&str>) -> Result<Vec<Vec<T>>> {
         // Post parameters
         let params = json!({
             "queries": queries,
@@ -105,28 +204,100 @@ impl Embeddings {
         });
 
         // Execute API call
-        Ok(self.api.post("batchsearch", &params).await?.json().await?)
+        self.api.post_json("batchsearch", &params).await
+    }
+
+    /// Runs an Embeddings search and returns the results as a graph. Requires an index with
+    /// a graph enabled. Nodes are the search results, edges connect related results.
+    ///
+    /// # Arguments
+    /// * `query` - query text
+    /// * `limit` - maximum results
+    /// * `weights` - hybrid score weights, if applicable
+    /// * `index` - index name, if applicable
+    pub async fn graph(&self, query: &str, limit: i32, weights: Option<f32>, index: Option<&str>) -> Result<Graph> {
+        let mut params = Embeddings::params(query, limit, weights, index);
+        params.push(("graph", "true".to_string()));
+
+        let params: Vec<(&str, &str)> = params.iter().map(|(name, value)| (*name, value.as_str())).collect();
+
+        // Execute API call and map JSON
+        self.api.get_json("search", &params).await
+    }
+
+    /// Runs a SQL query built with Query and maps each result into `T`. Use `Row` for
+    /// dynamic columns.
+    ///
+    /// # Arguments
+    /// * `query` - SQL query
+    pub async fn sql<T: DeserializeOwned>(&self, query: &Query) -> Result<Vec<T>> {
+        let limit = query.get_limit().unwrap_or(DEFAULT_LIMIT) as i32;
+        self.search_as(&query.to_sql(), limit, None, None).await
     }
 
-    /// Adds a batch of documents for indexing.
+    /// Runs a batch of SQL queries built with Query and maps each result into `T`. The
+    /// largest query limit is used as the batch limit.
+    ///
+    /// # Arguments
+    /// * `queries` - SQL queries
+    pub async fn batchsql<T: DeserializeOwned>(&self, queries: &[Query]) -> Result<Vec<Vec<T>>> {
+        let limit = queries.iter().map(|query| query.get_limit().unwrap_or(DEFAULT_LIMIT)).max().unwrap_or(DEFAULT_LIMIT) as i32;
+
+        let sql: Vec<String> = queries.iter().map(|query| query.to_sql()).collect();
+        self.batchsearch_as(&sql.iter().map(|x| x.as_str()).collect(), limit, None, None).await
+    }
+
+    /// Pages through the results of a SQL query. The query limit sets the page size and the
+    /// query offset sets the starting position.
+    ///
+    /// # Arguments
+    /// * `query` - SQL query
+    pub fn pages(&self, query: &Query) -> Pages {
+        Pages::sql(self.clone(), query)
+    }
+
+    /// Pages through the results of a text query.
+    ///
+    /// # Arguments
+    /// * `query` - query text
+    pub fn search_pages(&self, query: &str) -> Pages {
+        Pages::text(self.clone(), query)
+    }
+
+    /// Adds a batch of documents for indexing. Documents are validated before sending,
+    /// each must serialize to an object with an id and a text or object field.
     /// 
     /// # Arguments
-    /// * `documents` - list of {id: value, text: value}
+    /// * `documents` - list of {id: value, text: value, field: value, ...}
     pub async fn add<T: Serialize>(&self, documents: &Vec<T>) -> APIResponse {
+        let documents = serde_json::to_value(documents).map_err(|e| TxtaiError::InvalidInput(e.to_string()))?;
+
+        // Validate documents
+        if let Some(values) = documents.as_array() {
+            for document in values {
+                crate::document::validate(document)?;
+            }
+        }
+
         // Execute API call
-        Ok(self.api.post("add", &json!(documents)).await?)
+        self.api.post("add", &documents).await
+    }
+
+    /// Creates a Bulk instance for uploading large document collections in batches.
+    pub fn bulk(&self) -> Bulk {
+        Bulk::new(self.clone())
     }
 
     /// Builds an embeddings index for previously batched documents.
     pub async fn index(&self) -> APIResponse {
         // Execute API call
-        Ok(self.api.get("index", &[]).await?)
+        self.api.get("index", &[]).await
     }
 
     /// Runs an embeddings upsert operation for previously batched documents.
     pub async fn upsert(&self) -> APIResponse {
         // Execute API call
-        Ok(self.api.get("upsert", &[]).await?)
+        self.api.get("upsert", &[]).await
     }
 
      /// Deletes from an embeddings index. Returns list of ids deleted.
@@ -135,11 +306,18 @@ impl Embeddings {
      /// * `ids` - list of ids to delete
     pub async fn delete(&self, ids: &Vec<&str>) -> Ids {
         // Execute API call
-        Ok(self.api.post("delete", &json!(ids)).await?.json().await?)
+        self.api.post_json("delete", &json!(ids)).await
     }
 
     /// Recreates this embeddings index using config. This method only works if document content storage is enabled.
-    pub async fn reindex(&self, config: HashMap<&str, &str>, function: Option<&str>) -> APIResponse {
+    /// The configuration is validated before it's sent.
+    ///
+    /// # Arguments
+    /// * `config` - new embeddings configuration
+    /// * `function` - optional function to prepare content for indexing
+    pub async fn reindex(&self, config: &Config, function: Option<&str>) -> APIResponse {
+        config.validate()?;
+
         // Post parameters
         let params = json!({
             "config": config,
@@ -147,12 +325,12 @@ impl Embeddings {
         });
 
         // Execute API call
-        Ok(self.api.post("reindex", &params).await?)
+        self.api.post("reindex", &params).await
     }
 
     /// Total number of elements in this embeddings index.
     pub async fn count(&self) -> Count {
-        Ok(self.api.get("count", &[]).await?.json().await?)
+        self.api.get_json("count", &[]).await
     }
 
     /// Computes the similarity between query and list of text. Returns a list of
@@ -167,7 +345,7 @@ impl Embeddings {
         let params = json!({"query": query, "texts": texts});
 
         // Execute API call
-        Ok(self.api.post("similarity", &params).await?.json().await?)
+        self.api.post_json("similarity", &params).await
     }
 
     /// Computes the similarity between list of queries and list of text. Returns a list
@@ -182,28 +360,158 @@ impl Embeddings {
         let params = json!({"queries": queries, "texts": texts});
 
         // Execute API call
-        Ok(self.api.post("batchsimilarity", &params).await?.json().await?)
+        self.api.post_json("batchsimilarity", &params).await
+    }
+
+    /// Explains how each token in the top index results contributes to a query match.
+    ///
+    /// # Arguments
+    /// * `query` - input query
+    /// * `limit` - maximum results
+    pub async fn explain(&self, query: &str, limit: Option<usize>) -> Explanations {
+        // Query parameters
+        let mut params = vec![("query", query.to_string())];
+
+        if let Some(limit) = limit {
+            params.push(("limit", limit.to_string()));
+        }
+
+        let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+
+        // Execute API call
+        self.api.get_json("explain", &params).await
+    }
+
+    /// Explains a list of queries. Returns one list of explanations per query.
+    ///
+    /// # Arguments
+    /// * `queries` - list of queries
+    /// * `limit` - maximum results
+    pub async fn batchexplain(&self, queries: &[&str], limit: Option<usize>) -> ExplanationsBatch {
+        // Post parameters
+        let mut params = json!({"queries": queries});
+        if let Some(limit) = limit {
+            params["limit"] = json!(limit);
+        }
+
+        // Execute API call
+        self.api.post_json("batchexplain", &params).await
     }
 
     /// Transforms text into an embeddings array.
     /// 
     /// # Arguments
     /// * `text` - input text
-    pub async fn transform(&self, text: &str) -> Embedding {
+    /// * `category` - category for instruction-based embeddings, typically "query" or "data"
+    /// * `index` - index name, if applicable
+    pub async fn transform(&self, text: &str, category: Option<&str>, index: Option<&str>) -> Embedding {
+        // Check cache
+        let key = CacheKey::new(text, category, index);
+        if let Some(cache) = &self.cache {
+            if let Some(embedding) = cache.get(&key).await {
+                return Ok(embedding);
+            }
+        }
+
         // Query parameters
-        let params = [("text", text)];
+        let mut params = vec![("text", text)];
+
+        if let Some(category) = category {
+            params.push(("category", category));
+        }
+        if let Some(index) = index {
+            params.push(("index", index));
+        }
 
         // Execute API call
-        Ok(self.api.get("transform", &params).await?.json().await?)
+        let embedding: Vec<f32> = self.api.get_json("transform", &params).await?;
+
+        if let Some(cache) = &self.cache {
+            cache.put(&key, &embedding).await;
+        }
+
+        Ok(embedding)
     }
 
-    /// Transforms list of text into embeddings arrays.
+    /// Transforms list of text into embeddings arrays. Large inputs are split into multiple
+    /// requests of up to TRANSFORM_BATCH texts. Returns embeddings in input order.
     ///
     /// # Arguments
-    /// * `texts` - lists of text
-    pub async fn batchtransform(&self, texts: &str) -> EmbeddingBatch {
-        // Execute API call
-        Ok(self.api.post("batchtransform", &json!(texts)).await?.json().await?)
+    /// * `texts` - list of text
+    /// * `category` - category for instruction-based embeddings, typically "query" or "data"
+    /// * `index` - index name, if applicable
+    pub async fn batchtransform(&self, texts: &[&str], category: Option<&str>, index: Option<&str>) -> EmbeddingBatch {
+        // Query parameters
+        let mut params = Vec::new();
+
+        if let Some(category) = category {
+            params.push(("category", category));
+        }
+        if let Some(index) = index {
+            params.push(("index", index));
+        }
+
+        // Without a cache, all texts are sent to the API
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return self.batchtransform_api(texts, &params).await
+        };
+
+        // Look up cached embeddings, each distinct missing text is only sent once
+        let keys: Vec<CacheKey> = texts.iter().map(|text| CacheKey::new(text, category, index)).collect();
+        let embeddings: Vec<Option<Vec<f32>>> = future::join_all(keys.iter().map(|key| cache.get(key))).await;
+
+        let mut missing = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        for (x, text) in texts.iter().enumerate() {
+            if embeddings[x].is_none() && !positions.contains_key(text) {
+                positions.insert(text, missing.len());
+                missing.push(*text);
+            }
+        }
+
+        // Transform missing texts and store results
+        let results = self.batchtransform_api(&missing, &params).await?;
+
+        let stored: Vec<CacheKey> = missing.iter().map(|text| CacheKey::new(text, category, index)).collect();
+        future::join_all(stored.iter().enumerate().map(|(x, key)| cache.put(key, &results[x]))).await;
+
+        // Assemble embeddings in input order
+        let mut vectors = Vectors::default();
+        for (x, text) in texts.iter().enumerate() {
+            match &embeddings[x] {
+                Some(embedding) => vectors.push(embedding)?,
+                None => vectors.push(&results[positions[text]])?
+            }
+        }
+
+        Ok(vectors)
+    }
+
+    /// Sends texts to the batchtransform endpoint in chunks of up to TRANSFORM_BATCH texts.
+    /// Returns an error if a response doesn't have one embedding per text.
+    ///
+    /// # Arguments
+    /// * `texts` - list of text
+    /// * `params` - query parameters
+    async fn batchtransform_api(&self, texts: &[&str], params: &[(&str, &str)]) -> EmbeddingBatch {
+        let mut vectors = Vectors::default();
+        for batch in texts.chunks(TRANSFORM_BATCH) {
+            // Execute API call
+            let response = self.api.post_query("batchtransform", params, &json!(batch)).await?;
+            let embeddings: Vectors = API::decode(response).await?;
+
+            if embeddings.len() != batch.len() {
+                return Err(TxtaiError::Response {
+                    endpoint: "batchtransform".to_string(),
+                    detail: format!("expected {} embeddings, found {}", batch.len(), embeddings.len())
+                })
+            }
+
+            vectors.extend(&embeddings)?;
+        }
+
+        Ok(vectors)
     }
 
     /// Adds a batch of binary objects for indexing.
@@ -236,7 +544,7 @@ impl Embeddings {
         }
 
         // Execute API call
-        Ok(self.api.post_multipart("addobject", form).await?)
+        self.api.post_multipart("addobject", form).await
     }
 
     /// Adds a batch of images for indexing.
@@ -269,28 +577,134 @@ impl Embeddings {
         }
 
         // Execute API call
-        Ok(self.api.post_multipart("addimage", form).await?)
+        self.api.post_multipart("addimage", form).await
+    }
+
+    /// Builds search query parameters.
+    ///
+    /// # Arguments
+    /// * `query` - query text
+    /// * `limit` - maximum results
+    /// * `weights` - hybrid score weights, if applicable
+    /// * `index` - index name, if applicable
+    fn params(query: &str, limit: i32, weights: Option<f32>, index: Option<&str>) -> Vec<(&'static str, String)> {
+        let mut params = vec![("query", query.to_string()), ("limit", limit.to_string())];
+
+        if let Some(weights) = weights {
+            params.push(("weights", weights.to_string()));
+        }
+        if let Some(index) = index {
+            params.push(("index", index.to_string()));
+        }
+
+        params
     }
 }
 
-// Embeddings return types
-pub type Embedding = Result<Vec<f32>, Box<dyn Error>>;
-pub type EmbeddingBatch = Result<Vec<Vec<f32>>, Box<dyn Error>>;
-pub type Ids = Result<Vec<String>, Box<dyn Error>>;
-pub type Count = Result<usize, Box<dyn Error>>;
-pub type SearchResults = Result<Vec<SearchResult>, Box<dyn Error>>;
-pub type SearchResultsBatch = Result<Vec<Vec<SearchResult>>, Box<dyn Error>>;
-
-/// Input document
-#[derive(Debug, Serialize)]
-pub struct Document {
-    pub id: String,
-    pub text: String
+impl Default for Embeddings {
+    fn default() -> Self {
+        Embeddings::new()
+    }
 }
 
+// Embeddings return types
+pub type Embedding = Result<Vec<f32>>;
+pub type EmbeddingBatch = Result<Vectors>;
+pub type Ids = Result<Vec<String>>;
+pub type Count = Result<usize>;
+pub type SearchResults = Result<Vec<SearchResult>>;
+pub type SearchResultsBatch = Result<Vec<Vec<SearchResult>>>;
+pub type Explanations = Result<Vec<Explanation>>;
+pub type ExplanationsBatch = Result<Vec<Vec<Explanation>>>;
+pub type Rows = Result<Vec<Row>>;
+pub type RowsBatch = Result<Vec<Vec<Row>>>;
+
+/// Search result row, maps column names to values
+pub type Row = Map<String, Value>;
+
 // Search result
 #[derive(Debug, Deserialize)]
 pub struct SearchResult {
     pub id: String,
     pub score: f32
 }
+
+/// Explain result, scores a text and the importance of each of its tokens
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Explanation {
+    #[serde(deserialize_with = "identifier")]
+    pub id: String,
+    pub text: String,
+    pub score: f32,
+    pub tokens: Vec<(String, f32)>
+}
+
+/// Explanation implementation
+impl Explanation {
+    /// Gets the `top` highest weighted tokens, in descending weight order. Tokens with
+    /// a weight of zero or less are excluded.
+    ///
+    /// # Arguments
+    /// * `top` - maximum number of tokens
+    pub fn top(&self, top: usize) -> Vec<(&str, f32)> {
+        let mut tokens: Vec<(&str, f32)> = self.tokens.iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .map(|(token, weight)| (token.as_str(), *weight))
+            .collect();
+
+        tokens.sort_by(|a, b| b.1.total_cmp(&a.1));
+        tokens.truncate(top);
+        tokens
+    }
+
+    /// Renders the text with the `top` highest weighted tokens wrapped in `start` and `end`
+    /// markers, for example `<b>` and `</b>`. Tokens are matched in order, so repeated words
+    /// are only highlighted where they scored.
+    ///
+    /// # Arguments
+    /// * `top` - number of tokens to highlight
+    /// * `start` - marker inserted before each highlighted token
+    /// * `end` - marker inserted after each highlighted token
+    pub fn highlight(&self, top: usize, start: &str, end: &str) -> String {
+        // Select token positions to highlight
+        let mut positions: Vec<usize> = (0..self.tokens.len()).filter(|x| self.tokens[*x].1 > 0.0).collect();
+        positions.sort_by(|a, b| self.tokens[*b].1.total_cmp(&self.tokens[*a].1));
+        positions.truncate(top);
+
+        let mut output = String::with_capacity(self.text.len());
+        let mut cursor = 0;
+
+        for (x, (token, _)) in self.tokens.iter().enumerate() {
+            // Skip tokens that can't be located in the remaining text
+            let offset = match self.text[cursor..].find(token.as_str()) {
+                Some(offset) if !token.is_empty() => cursor + offset,
+                _ => continue
+            };
+
+            output.push_str(&self.text[cursor..offset]);
+            if positions.contains(&x) {
+                output.push_str(start);
+                output.push_str(token);
+                output.push_str(end);
+            }
+            else {
+                output.push_str(token);
+            }
+
+            cursor = offset + token.len();
+        }
+
+        output.push_str(&self.text[cursor..]);
+        output
+    }
+}
+
+/// Deserializes an id that is either a string or a number. Explain returns text positions
+/// when explaining a list of texts and document ids when searching the index.
+pub(crate) fn identifier<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<String, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(id) => Ok(id),
+        Value::Number(id) => Ok(id.to_string()),
+        other => Err(serde::de::Error::custom(format!("invalid id: {}", other)))
+    }
+}
@@ -0,0 +1,121 @@
+use std::error::Error;
+use std::fmt;
+
+/// Result type returned by all txtai methods
+pub type Result<T> = std::result::Result<T, TxtaiError>;
+
+/// txtai error definition
+#[derive(Debug)]
+pub enum TxtaiError {
+    /// Connection, protocol or request building failure
+    Transport(reqwest::Error),
+    /// Request did not complete before its connect, read or request timeout elapsed
+    Timeout {
+        endpoint: String,
+        source: Option<reqwest::Error>
+    },
+    /// Server returned a non-success HTTP status. Detail is the txtai error message,
+    /// or the raw response body when the server didn't send one.
+    Status {
+        endpoint: String,
+        status: u16,
+        detail: String
+    },
+    /// Server rejected the request credentials (HTTP 401 or 403)
+    Auth {
+        endpoint: String,
+        status: u16,
+        detail: String
+    },
+    /// Response body could not be decoded into the expected type
+    Decode {
+        source: serde_json::Error,
+        body: String
+    },
+    /// Response body was decoded but doesn't match the request, for example a batch
+    /// result with the wrong number of elements
+    Response {
+        endpoint: String,
+        detail: String
+    },
+    /// Input rejected before any request was sent
+    InvalidInput(String),
+    /// Local file read or write failure
+    Io(std::io::Error)
+}
+
+/// txtai error implementation
+impl TxtaiError {
+    /// Returns the HTTP status code, if this error was caused by a server response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            TxtaiError::Status { status, .. } | TxtaiError::Auth { status, .. } => Some(*status),
+            TxtaiError::Transport(e) => e.status().map(|s| s.as_u16()),
+            _ => None
+        }
+    }
+
+    /// Returns the API method that failed, if this error was caused by a server response or timeout.
+    pub fn endpoint(&self) -> Option<&str> {
+        match self {
+            TxtaiError::Status { endpoint, .. } | TxtaiError::Auth { endpoint, .. } | TxtaiError::Timeout { endpoint, .. }
+                | TxtaiError::Response { endpoint, .. } => Some(endpoint),
+            _ => None
+        }
+    }
+
+    /// Returns true if this error is a timeout.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, TxtaiError::Timeout { .. })
+    }
+}
+
+impl fmt::Display for TxtaiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxtaiError::Transport(e) => write!(f, "transport error: {}", e),
+            TxtaiError::Timeout { endpoint, .. } => write!(f, "{} timed out", endpoint),
+            TxtaiError::Status { endpoint, status, detail } => write!(f, "{} failed with HTTP status {}: {}", endpoint, status, detail),
+            TxtaiError::Auth { endpoint, status, detail } => write!(f, "{} authentication failed with HTTP status {}: {}", endpoint, status, detail),
+            TxtaiError::Decode { source, body } => write!(f, "failed to decode response: {} (body: {})", source, body),
+            TxtaiError::Response { endpoint, detail } => write!(f, "{} returned an unexpected response: {}", endpoint, detail),
+            TxtaiError::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            TxtaiError::Io(e) => write!(f, "I/O error: {}", e)
+        }
+    }
+}
+
+impl Error for TxtaiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TxtaiError::Transport(e) | TxtaiError::Timeout { source: Some(e), .. } => Some(e),
+            TxtaiError::Decode { source, .. } => Some(source),
+            TxtaiError::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<reqwest::Error> for TxtaiError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            // Name the endpoint using the last url path segment
+            let endpoint = error.url()
+                .and_then(|url| url.path_segments())
+                .and_then(|mut segments| segments.next_back())
+                .unwrap_or_default()
+                .to_string();
+
+            TxtaiError::Timeout { endpoint, source: Some(error) }
+        }
+        else {
+            TxtaiError::Transport(error)
+        }
+    }
+}
+
+impl From<std::io::Error> for TxtaiError {
+    fn from(error: std::io::Error) -> Self {
+        TxtaiError::Io(error)
+    }
+}
@@ -1,13 +1,13 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
 
-pub use crate::api::API;
+pub use crate::api::{API, Result};
 
 /// Extractor definition
 pub struct Extractor {
-    api: API
+    api: Arc<API>
 }
 
 /// Extractor implementation
@@ -16,7 +16,7 @@ impl Extractor {
     /// 
     pub fn new() -> Extractor {
         Extractor { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -26,7 +26,7 @@ impl Extractor {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Extractor {
         Extractor {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -37,7 +37,18 @@ impl Extractor {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Extractor {
         Extractor {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates an Extractor instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Extractor {
+        Extractor {
+            api: api.into()
         }
     }
 
@@ -51,12 +62,18 @@ impl Extractor {
         let params = json!({"queue": queue, "texts": texts});
 
         // Execute API call
-        Ok(self.api.post("extract", &params).await?.json().await?)
+        self.api.post_json("extract", &params).await
+    }
+}
+
+impl Default for Extractor {
+    fn default() -> Self {
+        Extractor::new()
     }
 }
 
 // Extractor return types
-pub type Answers = Result<Vec<Answer>, Box<dyn Error>>;
+pub type Answers = Result<Vec<Answer>>;
 
 /// Input Question
 #[derive(Debug, Serialize)]
@@ -0,0 +1,432 @@
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write;
+
+/// Graph search results definition. Nodes are search results, edges connect similar
+/// results and topics group nodes by topic. Graphs serialize in the networkx node-link
+/// format, with edges stored under `links`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Graph {
+    nodes: Vec<Node>,
+    #[serde(rename = "links")]
+    edges: Vec<Edge>,
+    topics: BTreeMap<String, Vec<String>>,
+    #[serde(skip)]
+    positions: HashMap<String, usize>
+}
+
+/// Graph node with its attributes, for example text, score, topic and centrality
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Node {
+    pub id: String,
+    #[serde(flatten)]
+    pub attributes: Map<String, Value>
+}
+
+/// Weighted, undirected graph edge
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Edge {
+    pub source: String,
+    pub target: String,
+    pub weight: f32,
+    #[serde(flatten)]
+    pub attributes: Map<String, Value>
+}
+
+/// Graph implementation
+impl Graph {
+    /// Creates a Graph. Topics not provided are built from node `topic` attributes.
+    ///
+    /// # Arguments
+    /// * `nodes` - list of nodes
+    /// * `edges` - list of edges
+    /// * `topics` - topic name to node ids
+    pub fn new(nodes: Vec<Node>, edges: Vec<Edge>, mut topics: BTreeMap<String, Vec<String>>) -> Graph {
+        if topics.is_empty() {
+            for node in &nodes {
+                if let Some(topic) = node.topic() {
+                    topics.entry(topic.to_string()).or_default().push(node.id.clone());
+                }
+            }
+        }
+
+        let positions = nodes.iter().enumerate().map(|(x, node)| (node.id.clone(), x)).collect();
+        Graph { nodes, edges, topics, positions }
+    }
+
+    /// Gets the nodes.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Gets the edges.
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Gets the topics, maps topic names to node ids.
+    pub fn topics(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.topics
+    }
+
+    /// Gets a node by id.
+    ///
+    /// # Arguments
+    /// * `id` - node id
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.positions.get(id).and_then(|x| self.nodes.get(*x))
+    }
+
+    /// Gets the nodes connected to a node along with the edge weights.
+    ///
+    /// # Arguments
+    /// * `id` - node id
+    pub fn neighbors(&self, id: &str) -> Vec<(&Node, f32)> {
+        self.edges.iter()
+            .filter_map(|edge| match (edge.source == id, edge.target == id) {
+                (true, _) => Some((edge.target.as_str(), edge.weight)),
+                (_, true) => Some((edge.source.as_str(), edge.weight)),
+                _ => None
+            })
+            .filter_map(|(neighbor, weight)| self.node(neighbor).map(|node| (node, weight)))
+            .collect()
+    }
+
+    /// Gets the nodes for a topic.
+    ///
+    /// # Arguments
+    /// * `topic` - topic name
+    pub fn topic(&self, topic: &str) -> Vec<&Node> {
+        self.topics.get(topic).into_iter().flatten().filter_map(|id| self.node(id)).collect()
+    }
+
+    /// Gets nodes sorted by descending centrality. When no node has a centrality attribute,
+    /// degree centrality is computed instead.
+    pub fn centrality(&self) -> Vec<(&Node, f64)> {
+        let provided = self.nodes.iter().any(|node| node.centrality().is_some());
+        let scale = self.nodes.len().saturating_sub(1).max(1) as f64;
+
+        let mut nodes: Vec<(&Node, f64)> = self.nodes.iter()
+            .map(|node| match provided {
+                true => (node, node.centrality().unwrap_or(0.0)),
+                false => (node, self.neighbors(&node.id).len() as f64 / scale)
+            })
+            .collect();
+
+        nodes.sort_by(|a, b| b.1.total_cmp(&a.1));
+        nodes
+    }
+
+    /// Traverses the graph breadth first starting at `start`. Returns nodes in visit order,
+    /// including the start node.
+    ///
+    /// # Arguments
+    /// * `start` - start node id
+    /// * `depth` - maximum number of edges from the start node
+    pub fn traverse(&self, start: &str, depth: usize) -> Vec<&Node> {
+        let mut visited = Vec::new();
+        let mut seen = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        if let Some(node) = self.node(start) {
+            seen.insert(node.id.as_str(), 0);
+            queue.push_back(node);
+        }
+
+        while let Some(node) = queue.pop_front() {
+            let level = seen[node.id.as_str()];
+            visited.push(node);
+
+            if level < depth {
+                for (neighbor, _) in self.neighbors(&node.id) {
+                    if !seen.contains_key(neighbor.id.as_str()) {
+                        seen.insert(neighbor.id.as_str(), level + 1);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        visited
+    }
+
+    /// Finds the path between two nodes that follows the most similar edges. Edge costs are
+    /// `1 - weight`, so a path through strongly connected nodes is preferred over a shorter
+    /// path through weak connections. Returns None if the nodes aren't connected.
+    ///
+    /// # Arguments
+    /// * `source` - source node id
+    /// * `target` - target node id
+    pub fn path(&self, source: &str, target: &str) -> Option<Vec<&Node>> {
+        let start = *self.positions.get(source)?;
+        let end = *self.positions.get(target)?;
+
+        // Dijkstra's algorithm, graphs from search results are small
+        let mut distances = vec![f32::INFINITY; self.nodes.len()];
+        let mut previous = vec![None; self.nodes.len()];
+        let mut done = vec![false; self.nodes.len()];
+        distances[start] = 0.0;
+
+        while let Some(current) = (0..self.nodes.len()).filter(|x| !done[*x] && distances[*x].is_finite()).min_by(|a, b| distances[*a].total_cmp(&distances[*b])) {
+            if current == end {
+                break;
+            }
+
+            done[current] = true;
+            for (neighbor, weight) in self.neighbors(&self.nodes[current].id) {
+                let x = self.positions[&neighbor.id];
+                let distance = distances[current] + (1.0 - weight).max(0.0);
+                if !done[x] && distance < distances[x] {
+                    distances[x] = distance;
+                    previous[x] = Some(current);
+                }
+            }
+        }
+
+        if !distances[end].is_finite() {
+            return None;
+        }
+
+        // Walk back from the target
+        let mut path = vec![&self.nodes[end]];
+        let mut current = end;
+        while let Some(x) = previous[current] {
+            path.push(&self.nodes[x]);
+            current = x;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    /// Exports this graph as GraphML. Scalar node and edge attributes are written as data
+    /// elements, other values are written as JSON strings.
+    pub fn to_graphml(&self) -> String {
+        let mut output = String::new();
+        output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        output.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+
+        // Attribute keys
+        let nodekeys = Graph::keys(self.nodes.iter().map(|node| &node.attributes));
+        let edgekeys = Graph::keys(self.edges.iter().map(|edge| &edge.attributes));
+
+        for (key, kind) in &nodekeys {
+            let _ = writeln!(output, "  <key id=\"n_{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"{1}\"/>", xml(key), kind);
+        }
+        output.push_str("  <key id=\"e_weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n");
+        for (key, kind) in &edgekeys {
+            let _ = writeln!(output, "  <key id=\"e_{0}\" for=\"edge\" attr.name=\"{0}\" attr.type=\"{1}\"/>", xml(key), kind);
+        }
+
+        output.push_str("  <graph edgedefault=\"undirected\">\n");
+        for node in &self.nodes {
+            let _ = writeln!(output, "    <node id=\"{}\">", xml(&node.id));
+            for (key, value) in &node.attributes {
+                let _ = writeln!(output, "      <data key=\"n_{}\">{}</data>", xml(key), xml(&scalar(value)));
+            }
+            output.push_str("    </node>\n");
+        }
+
+        for edge in &self.edges {
+            let _ = writeln!(output, "    <edge source=\"{}\" target=\"{}\">", xml(&edge.source), xml(&edge.target));
+            let _ = writeln!(output, "      <data key=\"e_weight\">{}</data>", edge.weight);
+            for (key, value) in &edge.attributes {
+                let _ = writeln!(output, "      <data key=\"e_{}\">{}</data>", xml(key), xml(&scalar(value)));
+            }
+            output.push_str("    </edge>\n");
+        }
+
+        output.push_str("  </graph>\n</graphml>\n");
+        output
+    }
+
+    /// Exports this graph in Graphviz DOT format. Nodes are labeled with their text when
+    /// available and edges with their weight.
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("graph {\n");
+
+        for node in &self.nodes {
+            let label = node.text().unwrap_or(&node.id);
+            let _ = write!(output, "  \"{}\" [label=\"{}\"", dot(&node.id), dot(label));
+            if let Some(topic) = node.topic() {
+                let _ = write!(output, ", topic=\"{}\"", dot(topic));
+            }
+            output.push_str("];\n");
+        }
+
+        for edge in &self.edges {
+            let _ = writeln!(output, "  \"{}\" -- \"{}\" [weight={}, label=\"{:.4}\"];", dot(&edge.source), dot(&edge.target), edge.weight, edge.weight);
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    /// Collects attribute names and their GraphML types.
+    fn keys<'a>(attributes: impl Iterator<Item = &'a Map<String, Value>>) -> BTreeMap<&'a str, &'static str> {
+        let mut keys = BTreeMap::new();
+        for map in attributes {
+            for (key, value) in map {
+                let kind = match value {
+                    Value::Bool(_) => "boolean",
+                    Value::Number(n) if n.is_f64() => "double",
+                    Value::Number(_) => "long",
+                    _ => "string"
+                };
+
+                // Mixed types fall back to string
+                let entry = keys.entry(key.as_str()).or_insert(kind);
+                if *entry != kind {
+                    *entry = "string";
+                }
+            }
+        }
+
+        keys
+    }
+}
+
+/// Node implementation
+impl Node {
+    /// Gets an attribute.
+    ///
+    /// # Arguments
+    /// * `name` - attribute name
+    pub fn attribute(&self, name: &str) -> Option<&Value> {
+        self.attributes.get(name)
+    }
+
+    /// Gets the node text, if available.
+    pub fn text(&self) -> Option<&str> {
+        self.attribute("text").and_then(|x| x.as_str())
+    }
+
+    /// Gets the node score, if available.
+    pub fn score(&self) -> Option<f64> {
+        self.attribute("score").and_then(|x| x.as_f64())
+    }
+
+    /// Gets the node topic, if available.
+    pub fn topic(&self) -> Option<&str> {
+        self.attribute("topic").and_then(|x| x.as_str())
+    }
+
+    /// Gets the node centrality, if available.
+    pub fn centrality(&self) -> Option<f64> {
+        self.attribute("centrality").and_then(|x| x.as_f64())
+    }
+}
+
+/// Deserializes graphs in networkx node-link format, `{"nodes": [{"id": ..}], "links": [..]}`,
+/// and in `{"nodes": [[id, {..}]], "edges": [[source, target, {..}]]}` format. Numeric ids
+/// are converted to strings.
+impl<'de> Deserialize<'de> for Graph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Graph, D::Error> {
+        let mut data = Map::deserialize(deserializer)?;
+
+        let nodes = match data.remove("nodes") {
+            Some(Value::Array(nodes)) => nodes.into_iter().map(node).collect::<Result<Vec<_>, String>>(),
+            Some(other) => Err(format!("invalid nodes: {}", other)),
+            None => Ok(Vec::new())
+        };
+
+        let edges = match data.remove("edges").or_else(|| data.remove("links")) {
+            Some(Value::Array(edges)) => edges.into_iter().map(edge).collect::<Result<Vec<_>, String>>(),
+            Some(other) => Err(format!("invalid edges: {}", other)),
+            None => Ok(Vec::new())
+        };
+
+        let topics = match data.remove("topics") {
+            Some(Value::Object(topics)) => topics.into_iter()
+                .map(|(name, ids)| match ids {
+                    Value::Array(ids) => Ok((name, ids.iter().map(identifier).collect())),
+                    other => Err(format!("invalid topic {}: {}", name, other))
+                })
+                .collect::<Result<BTreeMap<_, _>, String>>(),
+            Some(Value::Null) | None => Ok(BTreeMap::new()),
+            Some(other) => Err(format!("invalid topics: {}", other))
+        };
+
+        let mut graph = Graph::new(nodes.map_err(de::Error::custom)?, edges.map_err(de::Error::custom)?, topics.map_err(de::Error::custom)?);
+
+        // Merge a separate centrality map into node attributes
+        if let Some(Value::Object(centrality)) = data.remove("centrality") {
+            for (id, value) in centrality {
+                if let Some(x) = graph.positions.get(&id) {
+                    graph.nodes[*x].attributes.insert("centrality".to_string(), value);
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+/// Parses a node from a `{"id": .., ..}` object or an `[id, {..}]` pair.
+fn node(value: Value) -> Result<Node, String> {
+    match value {
+        Value::Object(mut attributes) => {
+            let id = attributes.remove("id").ok_or("node missing id")?;
+            Ok(Node { id: identifier(&id), attributes })
+        }
+        Value::Array(mut pair) if pair.len() == 2 => {
+            let attributes = match pair.pop() {
+                Some(Value::Object(attributes)) => attributes,
+                _ => Map::new()
+            };
+            Ok(Node { id: identifier(&pair[0]), attributes })
+        }
+        other => Err(format!("invalid node: {}", other))
+    }
+}
+
+/// Parses an edge from a `{"source": .., "target": .., ..}` object or a
+/// `[source, target, {..}]` triple.
+fn edge(value: Value) -> Result<Edge, String> {
+    let (source, target, mut attributes) = match value {
+        Value::Object(mut attributes) => {
+            let source = attributes.remove("source").ok_or("edge missing source")?;
+            let target = attributes.remove("target").ok_or("edge missing target")?;
+            (source, target, attributes)
+        }
+        Value::Array(mut triple) if triple.len() == 2 || triple.len() == 3 => {
+            let attributes = match triple.get_mut(2).map(Value::take) {
+                Some(Value::Object(attributes)) => attributes,
+                _ => Map::new()
+            };
+            (triple[0].take(), triple[1].take(), attributes)
+        }
+        other => return Err(format!("invalid edge: {}", other))
+    };
+
+    let weight = attributes.remove("weight").and_then(|x| x.as_f64()).unwrap_or(1.0) as f32;
+    Ok(Edge { source: identifier(&source), target: identifier(&target), weight, attributes })
+}
+
+/// Converts an id value to a string.
+fn identifier(value: &Value) -> String {
+    match value {
+        Value::String(id) => id.clone(),
+        other => other.to_string()
+    }
+}
+
+/// Converts an attribute value to text.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string()
+    }
+}
+
+/// Escapes XML text.
+fn xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+/// Escapes a DOT quoted string.
+fn dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
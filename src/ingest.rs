@@ -0,0 +1,475 @@
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::api::{Text, API};
+use crate::document::Document;
+use crate::embeddings::Embeddings;
+use crate::error::{Result, TxtaiError};
+use crate::segmentation::Segmentation;
+use crate::textractor::Textractor;
+
+/// Ingest definition. Indexes every file under a directory: extracts text with Textractor,
+/// optionally splits it with Segmentation, adds each chunk to an embeddings index and runs
+/// an upsert.
+///
+/// Text extraction runs on the txtai server, so files must be readable by the server at the
+/// same path, for example when the client and server share a host or a mounted volume.
+pub struct Ingest {
+    textractor: Textractor,
+    segmentation: Segmentation,
+    embeddings: Embeddings,
+    segment: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    checkpoint: Option<PathBuf>,
+    interval: usize,
+    concurrency: usize
+}
+
+/// Ingest implementation
+impl Ingest {
+    /// Creates an Ingest instance.
+    ///
+    pub fn new() -> Ingest {
+        Ingest::with_api(API::new())
+    }
+
+    /// Creates an Ingest instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    pub fn with_url(url: &str) -> Ingest {
+        Ingest::with_api(API::with_url(url))
+    }
+
+    /// Creates an Ingest instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    /// * `token` - API token
+    pub fn with_url_token(url: &str, token: &str) -> Ingest {
+        Ingest::with_api(API::with_url_token(url, token))
+    }
+
+    /// Creates an Ingest instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Ingest {
+        let api = api.into();
+
+        Ingest {
+            textractor: Textractor::with_api(api.clone()),
+            segmentation: Segmentation::with_api(api.clone()),
+            embeddings: Embeddings::with_api(api),
+            segment: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            checkpoint: None,
+            interval: 100,
+            concurrency: 4
+        }
+    }
+
+    /// Splits extracted text into semantic units with Segmentation before indexing.
+    ///
+    /// # Arguments
+    /// * `segment` - segment text if true
+    pub fn segment(mut self, segment: bool) -> Ingest {
+        self.segment = segment;
+        self
+    }
+
+    /// Adds an include glob. Files are included when they match any include glob, all
+    /// files are included when no globs are set. See `matches` for glob syntax.
+    ///
+    /// # Arguments
+    /// * `glob` - glob pattern
+    pub fn include(mut self, glob: &str) -> Ingest {
+        self.include.push(glob.to_string());
+        self
+    }
+
+    /// Adds an exclude glob. Files matching any exclude glob are skipped.
+    ///
+    /// # Arguments
+    /// * `glob` - glob pattern
+    pub fn exclude(mut self, glob: &str) -> Ingest {
+        self.exclude.push(glob.to_string());
+        self
+    }
+
+    /// Sets a checkpoint file. Files are recorded in the checkpoint once they are upserted
+    /// and skipped when the ingestion is run again.
+    ///
+    /// # Arguments
+    /// * `path` - checkpoint file path
+    pub fn checkpoint(mut self, path: impl AsRef<Path>) -> Ingest {
+        self.checkpoint = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the number of files processed between upserts and checkpoint updates.
+    ///
+    /// # Arguments
+    /// * `interval` - number of files
+    pub fn interval(mut self, interval: usize) -> Ingest {
+        self.interval = interval.max(1);
+        self
+    }
+
+    /// Sets the maximum number of files extracted concurrently.
+    ///
+    /// # Arguments
+    /// * `concurrency` - maximum concurrent files
+    pub fn concurrency(mut self, concurrency: usize) -> Ingest {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Indexes all matching files under a directory. Files that fail to extract or upload
+    /// and upserts that fail are listed in the report and don't stop the ingestion. Errors
+    /// reading the directory or writing the checkpoint are returned immediately.
+    ///
+    /// # Arguments
+    /// * `directory` - directory to index
+    pub async fn run(&self, directory: impl AsRef<Path>) -> Result<IngestReport> {
+        let root = tokio::fs::canonicalize(directory).await?;
+
+        let mut checkpoint = match &self.checkpoint {
+            Some(path) => Checkpoint::load(path).await?,
+            None => Checkpoint::default()
+        };
+
+        let mut report = IngestReport::default();
+
+        // Select files
+        let mut files = Vec::new();
+        for (path, name) in walk(&root).await? {
+            if !self.selected(&name) {
+                continue
+            }
+
+            if checkpoint.files.contains(&name) {
+                report.skipped += 1;
+            }
+            else {
+                files.push((path, name));
+            }
+        }
+
+        for group in files.chunks(self.interval) {
+            let results: Vec<(String, Result<usize>)> = stream::iter(group.iter().cloned())
+                .map(|(path, name)| async move {
+                    let result = self.file(&path, &name).await;
+                    (name, result)
+                })
+                .buffer_unordered(self.concurrency)
+                .collect()
+                .await;
+
+            let mut indexed = Vec::new();
+            for (name, result) in results {
+                match result {
+                    Ok(chunks) => indexed.push(IndexedFile { path: name, chunks }),
+                    Err(error) => report.failed.push(FailedFile { path: name, error })
+                }
+            }
+
+            if indexed.is_empty() {
+                continue
+            }
+
+            // Commit group, files are only reported as indexed once the upsert succeeds
+            if let Err(error) = self.embeddings.upsert().await {
+                report.upserts.push(UpsertFailure { paths: indexed.into_iter().map(|x| x.path).collect(), error });
+                continue
+            }
+
+            // Record progress
+            if let Some(path) = &self.checkpoint {
+                checkpoint.files.extend(indexed.iter().map(|x| x.path.clone()));
+                checkpoint.save(path).await?;
+            }
+
+            report.chunks += indexed.iter().map(|x| x.chunks).sum::<usize>();
+            report.indexed.extend(indexed);
+        }
+
+        Ok(report)
+    }
+
+    /// Extracts, segments and adds a single file. Returns the number of chunks added.
+    ///
+    /// # Arguments
+    /// * `path` - full file path
+    /// * `name` - file path relative to the root directory
+    async fn file(&self, path: &str, name: &str) -> Result<usize> {
+        let mut chunks = texts(self.textractor.textract(path).await?);
+
+        if self.segment && !chunks.is_empty() {
+            let segments = self.segmentation.batchsegment(&chunks.iter().map(|x| x.as_str()).collect()).await?;
+            chunks = segments.into_iter().flat_map(texts).collect();
+        }
+
+        let documents: Vec<Document> = chunks.iter().enumerate().map(|(offset, text)| {
+            Document::new(&format!("{}#{}", name, offset), text)
+                .object(path)
+                .with("path", name)
+                .with("offset", offset)
+        }).collect();
+
+        if !documents.is_empty() {
+            self.embeddings.add(&documents).await?;
+        }
+
+        Ok(documents.len())
+    }
+
+    /// Checks if a file is selected by the include and exclude globs.
+    ///
+    /// # Arguments
+    /// * `name` - file path relative to the root directory
+    fn selected(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| matches(glob, name)))
+            && !self.exclude.iter().any(|glob| matches(glob, name))
+    }
+}
+
+impl Default for Ingest {
+    fn default() -> Self {
+        Ingest::new()
+    }
+}
+
+/// File that was indexed
+#[derive(Debug)]
+pub struct IndexedFile {
+    /// File path relative to the root directory
+    pub path: String,
+    /// Number of chunks indexed
+    pub chunks: usize
+}
+
+/// File that failed to index
+#[derive(Debug)]
+pub struct FailedFile {
+    /// File path relative to the root directory
+    pub path: String,
+    /// Extraction or upload error
+    pub error: TxtaiError
+}
+
+/// Group of files that were added but not committed because the upsert failed
+#[derive(Debug)]
+pub struct UpsertFailure {
+    /// File paths relative to the root directory
+    pub paths: Vec<String>,
+    /// Upsert error
+    pub error: TxtaiError
+}
+
+/// Ingestion report
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// Files indexed
+    pub indexed: Vec<IndexedFile>,
+    /// Files that failed
+    pub failed: Vec<FailedFile>,
+    /// Upserts that failed, these files aren't indexed or checkpointed
+    pub upserts: Vec<UpsertFailure>,
+    /// Number of files skipped because they were already in the checkpoint
+    pub skipped: usize,
+    /// Total number of chunks indexed
+    pub chunks: usize
+}
+
+impl fmt::Display for IngestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uncommitted: usize = self.upserts.iter().map(|x| x.paths.len()).sum();
+        write!(f, "{} files indexed ({} chunks), {} failed, {} skipped", self.indexed.len(), self.chunks, self.failed.len() + uncommitted, self.skipped)
+    }
+}
+
+/// Ingestion checkpoint, lists files that have been upserted
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Checkpoint {
+    files: BTreeSet<String>
+}
+
+/// Checkpoint implementation
+impl Checkpoint {
+    /// Loads a checkpoint. Returns an empty checkpoint if the file doesn't exist.
+    ///
+    /// # Arguments
+    /// * `path` - checkpoint file path
+    async fn load(path: &Path) -> Result<Checkpoint> {
+        match tokio::fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|e| TxtaiError::InvalidInput(format!("checkpoint {}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Checkpoint::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    /// Saves a checkpoint. The file is written to a temporary path and renamed so an
+    /// interrupted write never leaves a partial checkpoint.
+    ///
+    /// # Arguments
+    /// * `path` - checkpoint file path
+    async fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_vec_pretty(self).map_err(|e| TxtaiError::InvalidInput(e.to_string()))?;
+
+        let temp = path.with_extension("tmp");
+        tokio::fs::write(&temp, data).await?;
+        tokio::fs::rename(&temp, path).await?;
+
+        Ok(())
+    }
+}
+
+/// Flattens a Text result into a list of non-empty strings.
+///
+/// # Arguments
+/// * `text` - text result
+fn texts(text: Text) -> Vec<String> {
+    let texts = match text {
+        Text::String(text) => vec![text],
+        Text::List(texts) => texts
+    };
+
+    texts.into_iter().filter(|x| !x.trim().is_empty()).collect()
+}
+
+/// Lists all files under a directory, sorted by path. Returns (full path, relative path)
+/// tuples, relative paths always use / as the separator.
+///
+/// # Arguments
+/// * `root` - root directory
+async fn walk(root: &Path) -> Result<Vec<(String, String)>> {
+    let mut files = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        let mut entries = tokio::fs::read_dir(&directory).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                directories.push(path);
+            }
+            else if let Ok(relative) = path.strip_prefix(root) {
+                let name = relative.components().map(|x| x.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                files.push((path.to_string_lossy().to_string(), name));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Matches a relative file path against a glob. `*` matches any characters except `/`,
+/// `**` matches any characters including `/` and `?` matches a single character. Globs
+/// without a `/` are matched against the file name only.
+///
+/// # Arguments
+/// * `glob` - glob pattern
+/// * `path` - relative file path
+pub fn matches(glob: &str, path: &str) -> bool {
+    let path = if glob.contains('/') { path } else { path.rsplit('/').next().unwrap_or(path) };
+
+    let glob: Vec<char> = glob.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+
+    let mut memo = vec![None; (glob.len() + 1) * (path.len() + 1)];
+    wildcard(&glob, &path, 0, 0, &mut memo)
+}
+
+/// Recursive glob matcher. Results are memoized on (glob position, path position), which
+/// keeps globs with many wildcards polynomial.
+///
+/// # Arguments
+/// * `glob` - glob characters
+/// * `path` - path characters
+/// * `g` - current glob position
+/// * `p` - current path position
+/// * `memo` - cached results
+fn wildcard(glob: &[char], path: &[char], g: usize, p: usize, memo: &mut [Option<bool>]) -> bool {
+    let key = g * (path.len() + 1) + p;
+    if let Some(result) = memo[key] {
+        return result
+    }
+
+    let result = match glob.get(g) {
+        None => p == path.len(),
+        Some('*') if glob.get(g + 1) == Some(&'*') => {
+            // **/ also matches zero directories
+            (glob.get(g + 2) == Some(&'/') && wildcard(glob, path, g + 3, p, memo))
+                || (p..=path.len()).any(|i| wildcard(glob, path, g + 2, i, memo))
+        },
+        Some('*') => {
+            (p..=path.len()).take_while(|&i| i == p || path[i - 1] != '/').any(|i| wildcard(glob, path, g + 1, i, memo))
+        },
+        Some('?') => p < path.len() && path[p] != '/' && wildcard(glob, path, g + 1, p + 1, memo),
+        Some(c) => path.get(p) == Some(c) && wildcard(glob, path, g + 1, p + 1, memo)
+    };
+
+    memo[key] = Some(result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails to compile if T isn't Send.
+    fn send<T: Send>(_: T) {}
+
+    #[test]
+    fn recursive_globs() {
+        assert!(matches("**/*.pdf", "a.pdf"));
+        assert!(matches("**/*.pdf", "docs/a.pdf"));
+        assert!(matches("**/*.pdf", "docs/sub/a.pdf"));
+        assert!(matches("docs/**/*.pdf", "docs/a.pdf"));
+        assert!(matches("docs/**/*.pdf", "docs/sub/deep/a.pdf"));
+        assert!(!matches("docs/**/*.pdf", "other/a.pdf"));
+        assert!(matches("docs/**", "docs/sub/a.txt"));
+    }
+
+    #[test]
+    fn basename_globs() {
+        assert!(matches("*.pdf", "a.pdf"));
+        assert!(matches("*.pdf", "docs/sub/a.pdf"));
+        assert!(!matches("*.pdf", "docs.pdf/a.txt"));
+        assert!(matches("skip.txt", "docs/skip.txt"));
+        assert!(!matches("docs/*.pdf", "docs/sub/a.pdf"));
+        assert!(matches("docs/*.pdf", "docs/a.pdf"));
+    }
+
+    #[test]
+    fn single_characters() {
+        assert!(matches("a?.txt", "ab.txt"));
+        assert!(!matches("a?.txt", "a.txt"));
+        assert!(!matches("docs?a.txt", "docs/a.txt"));
+        assert!(matches("docs/?.txt", "docs/a.txt"));
+    }
+
+    #[test]
+    fn repeated_wildcards() {
+        // Exponential without memoization
+        let glob = "**a".repeat(12) + "**b";
+        assert!(!matches(&glob, &"a".repeat(40)));
+        assert!(matches(&glob, &("a".repeat(40) + "b")));
+    }
+
+    #[test]
+    fn run_is_send() {
+        let ingest = Ingest::with_url("http://localhost:8000");
+        send(ingest.run("."));
+    }
+}
@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, IndexResults, IndexResultsBatch};
 
 /// Labels definition
 pub struct Labels {
-    api: API
+    api: Arc<API>
 }
 
 /// Labels implementation
@@ -13,7 +14,7 @@ impl Labels {
     /// 
     pub fn new() -> Labels {
         Labels { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Labels {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Labels {
         Labels {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Labels {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Labels {
         Labels {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Labels instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Labels {
+        Labels {
+            api: api.into()
         }
     }
 
@@ -49,7 +61,7 @@ impl Labels {
         let params = json!({"text": text, "labels": labels});
 
         // Execute API call
-        Ok(self.api.post("label", &params).await?.json().await?)
+        self.api.post_json("label", &params).await
     }
 
     /// Applies a zero shot classifier to list of text using a list of labels. Returns a list of
@@ -64,6 +76,12 @@ impl Labels {
         let params = json!({"texts": texts, "labels": labels});
 
         // Execute API call
-        Ok(self.api.post("batchlabel", &params).await?.json().await?)
+        self.api.post_json("batchlabel", &params).await
+    }
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Labels::new()
     }
 }
@@ -1,11 +1,30 @@
 pub mod api;
+pub mod builder;
+pub mod bulk;
+pub mod cache;
+pub mod caption;
+pub mod client;
+pub mod config;
+pub mod document;
 pub mod embeddings;
+pub mod error;
 pub mod extractor;
+pub mod graph;
+pub mod ingest;
 pub mod labels;
+pub mod llm;
+pub mod pages;
+pub mod query;
+pub mod rag;
+pub mod reader;
+pub mod retry;
 pub mod segmentation;
 pub mod similarity;
 pub mod summary;
 pub mod textractor;
 pub mod transcription;
 pub mod translation;
-pub mod workflow;
\ No newline at end of file
+pub mod vectors;
+pub mod workflow;
+
+pub use client::Txtai;
@@ -0,0 +1,436 @@
+use futures::stream::{BoxStream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+pub use crate::api::{API, Result, Strings, StringsBatch};
+
+/// LLM definition
+#[derive(Clone)]
+pub struct LLM {
+    api: Arc<API>
+}
+
+/// LLM implementation
+impl LLM {
+    /// Creates a LLM instance.
+    ///
+    pub fn new() -> LLM {
+        LLM {
+            api: Arc::new(API::new())
+        }
+    }
+
+    /// Creates a LLM instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    pub fn with_url(url: &str) -> LLM {
+        LLM {
+            api: Arc::new(API::with_url(url))
+        }
+    }
+
+    /// Creates a LLM instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    /// * `token` - API token
+    pub fn with_url_token(url: &str, token: &str) -> LLM {
+        LLM {
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a LLM instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> LLM {
+        LLM {
+            api: api.into()
+        }
+    }
+
+    /// Runs a LLM prompt.
+    ///
+    /// # Arguments
+    /// * `text` - prompt text
+    /// * `generation` - generation parameters
+    pub async fn llm(&self, text: &str, generation: &Generation) -> Strings {
+        // Query parameters
+        let params = generation.query(text);
+        let params: Vec<(&str, &str)> = params.iter().map(|(name, value)| (*name, value.as_str())).collect();
+
+        // Execute API call
+        self.api.get_json("llm", &params).await
+    }
+
+    /// Runs a LLM chat conversation. Returns the next assistant message.
+    ///
+    /// # Arguments
+    /// * `messages` - list of chat messages
+    /// * `generation` - generation parameters
+    pub async fn chat(&self, messages: &[Message], generation: &Generation) -> Strings {
+        // Post parameters
+        let params = generation.body("text", json!(messages));
+
+        // Execute API call
+        self.api.post_json("llm", &params).await
+    }
+
+    /// Runs a LLM prompt and streams the output as it's generated. Dropping the returned
+    /// stream closes the connection and stops reading the response.
+    ///
+    /// # Arguments
+    /// * `text` - prompt text
+    /// * `generation` - generation parameters
+    pub async fn stream(&self, text: &str, generation: &Generation) -> Result<TokenStream> {
+        // Query parameters
+        let mut params = generation.query(text);
+        params.push(("stream", "true".to_string()));
+
+        let params: Vec<(&str, &str)> = params.iter().map(|(name, value)| (*name, value.as_str())).collect();
+
+        // Execute API call
+        let response = self.api.get("llm", &params).await?;
+        Ok(TokenStream::new(response))
+    }
+
+    /// Runs a LLM chat conversation and streams the next assistant message as it's generated.
+    /// Dropping the returned stream closes the connection and stops reading the response.
+    ///
+    /// # Arguments
+    /// * `messages` - list of chat messages
+    /// * `generation` - generation parameters
+    pub async fn stream_chat(&self, messages: &[Message], generation: &Generation) -> Result<TokenStream> {
+        // Post parameters
+        let mut params = generation.body("text", json!(messages));
+        params["stream"] = json!(true);
+
+        // Execute API call
+        let response = self.api.post("llm", &params).await?;
+        Ok(TokenStream::new(response))
+    }
+
+    /// Runs a batch of LLM prompts and chat conversations. Returns one output per prompt.
+    ///
+    /// # Arguments
+    /// * `prompts` - list of prompts
+    /// * `generation` - generation parameters
+    pub async fn batchllm(&self, prompts: &[Prompt], generation: &Generation) -> StringsBatch {
+        // Post parameters
+        let params = generation.body("texts", json!(prompts));
+
+        // Execute API call
+        self.api.post_json("batchllm", &params).await
+    }
+}
+
+impl Default for LLM {
+    fn default() -> Self {
+        LLM::new()
+    }
+}
+
+/// Stream of generated text chunks. Chunks are decoded as UTF-8, characters split across
+/// network reads are held back until complete.
+pub struct TokenStream {
+    inner: BoxStream<'static, Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    done: bool
+}
+
+/// TokenStream implementation
+impl TokenStream {
+    /// Creates a TokenStream that reads a response body.
+    ///
+    /// # Arguments
+    /// * `response` - streaming response
+    pub fn new(response: reqwest::Response) -> TokenStream {
+        TokenStream {
+            inner: response.bytes_stream().map(|chunk| chunk.map(|x| x.to_vec()).map_err(Into::into)).boxed(),
+            buffer: Vec::new(),
+            done: false
+        }
+    }
+
+    /// Reads the remaining stream and returns the full text.
+    pub async fn text(mut self) -> Result<String> {
+        let mut text = String::new();
+        while let Some(chunk) = self.next().await {
+            text.push_str(&chunk?);
+        }
+
+        Ok(text)
+    }
+
+    /// Takes the decoded text from the buffer. Incomplete characters stay buffered unless
+    /// `finish` is set, invalid bytes are replaced with U+FFFD.
+    fn decode(&mut self, finish: bool) -> Option<String> {
+        let mut text = String::new();
+
+        loop {
+            match std::str::from_utf8(&self.buffer) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    self.buffer.clear();
+                    break;
+                }
+                Err(error) => {
+                    let valid = error.valid_up_to();
+                    text.push_str(std::str::from_utf8(&self.buffer[..valid]).unwrap_or_default());
+
+                    match error.error_len() {
+                        Some(length) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            self.buffer.drain(..valid + length);
+                        }
+                        None if finish => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            self.buffer.clear();
+                            break;
+                        }
+                        None => {
+                            self.buffer.drain(..valid);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+impl Stream for TokenStream {
+    type Item = Result<String>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        while !self.done {
+            match self.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(bytes))) => {
+                    self.buffer.extend_from_slice(&bytes);
+                    if let Some(text) = self.decode(false) {
+                        return Poll::Ready(Some(Ok(text)));
+                    }
+                }
+                Poll::Ready(Some(Err(error))) => {
+                    self.done = true;
+                    return Poll::Ready(Some(Err(error)));
+                }
+                Poll::Ready(None) => {
+                    self.done = true;
+                    return Poll::Ready(self.decode(true).map(Ok));
+                }
+                Poll::Pending => return Poll::Pending
+            }
+        }
+
+        Poll::Ready(None)
+    }
+}
+
+/// Generation parameters definition. Unset parameters use the server defaults.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Generation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxlength: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaultrole: Option<String>
+}
+
+/// Generation implementation
+impl Generation {
+    /// Creates Generation parameters with server defaults.
+    pub fn new() -> Generation {
+        Generation::default()
+    }
+
+    /// Sets the maximum sequence length.
+    ///
+    /// # Arguments
+    /// * `maxlength` - maximum sequence length
+    pub fn maxlength(mut self, maxlength: usize) -> Generation {
+        self.maxlength = Some(maxlength);
+        self
+    }
+
+    /// Sets strings that stop generation.
+    ///
+    /// # Arguments
+    /// * `stop` - list of stop strings
+    pub fn stop(mut self, stop: &[&str]) -> Generation {
+        self.stop = Some(stop.iter().map(|x| x.to_string()).collect());
+        self
+    }
+
+    /// Sets the sampling temperature.
+    ///
+    /// # Arguments
+    /// * `temperature` - sampling temperature
+    pub fn temperature(mut self, temperature: f64) -> Generation {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Sets the role applied to plain text prompts, for example "user" to apply the chat
+    /// template or "prompt" to pass text through unchanged.
+    ///
+    /// # Arguments
+    /// * `defaultrole` - default role
+    pub fn defaultrole(mut self, defaultrole: &str) -> Generation {
+        self.defaultrole = Some(defaultrole.to_string());
+        self
+    }
+
+    /// Builds query parameters for a text prompt.
+    pub(crate) fn query(&self, text: &str) -> Vec<(&'static str, String)> {
+        let mut params = vec![("text", text.to_string())];
+
+        if let Some(maxlength) = self.maxlength {
+            params.push(("maxlength", maxlength.to_string()));
+        }
+        for stop in self.stop.iter().flatten() {
+            params.push(("stop", stop.clone()));
+        }
+        if let Some(temperature) = self.temperature {
+            params.push(("temperature", temperature.to_string()));
+        }
+        if let Some(defaultrole) = &self.defaultrole {
+            params.push(("defaultrole", defaultrole.clone()));
+        }
+
+        params
+    }
+
+    /// Builds a request body with `input` stored under `name`.
+    pub(crate) fn body(&self, name: &str, input: Value) -> Value {
+        let mut body = json!(self);
+        body[name] = input;
+        body
+    }
+}
+
+/// Chat message role
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant
+}
+
+/// Chat message
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: String
+}
+
+/// Message implementation
+impl Message {
+    /// Creates a Message.
+    ///
+    /// # Arguments
+    /// * `role` - message role
+    /// * `content` - message content
+    pub fn new(role: Role, content: &str) -> Message {
+        Message { role, content: content.to_string() }
+    }
+
+    /// Creates a system message.
+    pub fn system(content: &str) -> Message {
+        Message::new(Role::System, content)
+    }
+
+    /// Creates a user message.
+    pub fn user(content: &str) -> Message {
+        Message::new(Role::User, content)
+    }
+
+    /// Creates an assistant message.
+    pub fn assistant(content: &str) -> Message {
+        Message::new(Role::Assistant, content)
+    }
+}
+
+/// LLM input, either a text prompt or a list of chat messages
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Prompt {
+    Text(String),
+    Chat(Vec<Message>)
+}
+
+impl From<&str> for Prompt {
+    fn from(text: &str) -> Prompt {
+        Prompt::Text(text.to_string())
+    }
+}
+
+impl From<String> for Prompt {
+    fn from(text: String) -> Prompt {
+        Prompt::Text(text)
+    }
+}
+
+impl From<Vec<Message>> for Prompt {
+    fn from(messages: Vec<Message>) -> Prompt {
+        Prompt::Chat(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+    use crate::error::TxtaiError;
+
+    /// Creates a TokenStream that reads a list of chunks.
+    fn tokens(chunks: Vec<Result<Vec<u8>>>) -> TokenStream {
+        TokenStream { inner: stream::iter(chunks).boxed(), buffer: Vec::new(), done: false }
+    }
+
+    /// Reads all chunks from a TokenStream.
+    async fn read(chunks: Vec<&[u8]>) -> Vec<String> {
+        tokens(chunks.into_iter().map(|x| Ok(x.to_vec())).collect()).map(|x| x.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn split_characters() {
+        // "é" is 0xC3 0xA9 and "😀" is 0xF0 0x9F 0x98 0x80
+        assert_eq!(read(vec![b"caf\xc3", b"\xa9 ok"]).await, vec!["caf", "é ok"]);
+        assert_eq!(read(vec![b"\xf0\x9f", b"\x98", b"\x80!"]).await, vec!["😀!"]);
+    }
+
+    #[tokio::test]
+    async fn invalid_bytes() {
+        assert_eq!(read(vec![b"a\xffb"]).await, vec!["a\u{fffd}b"]);
+        assert_eq!(read(vec![b"a\xc3", b"b"]).await, vec!["a", "\u{fffd}b"]);
+
+        // Incomplete characters at the end of the stream
+        assert_eq!(read(vec![b"end\xe2\x82"]).await, vec!["end", "\u{fffd}"]);
+    }
+
+    #[tokio::test]
+    async fn errors_end_stream() {
+        let chunks = vec![Ok(b"a".to_vec()), Err(TxtaiError::InvalidInput("reset".to_string())), Ok(b"b".to_vec())];
+        let results: Vec<Result<String>> = tokens(chunks).collect().await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_deref().unwrap(), "a");
+        assert!(results[1].is_err());
+
+        assert_eq!(tokens(vec![Ok("full text".as_bytes().to_vec())]).text().await.unwrap(), "full text");
+    }
+}
@@ -0,0 +1,209 @@
+use futures::stream::{self, Stream};
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::embeddings::{Embeddings, Row};
+use crate::error::Result;
+use crate::query::{Query, DEFAULT_LIMIT};
+
+/// Pages definition. Fetches search results one page at a time. SQL queries are paged with
+/// limit/offset, plain text queries are paged by growing the limit and skipping results
+/// already returned. Ids are deduplicated across pages.
+#[derive(Clone)]
+pub struct Pages {
+    embeddings: Embeddings,
+    source: Source,
+    size: usize,
+    max: Option<usize>,
+    weights: Option<f32>,
+    index: Option<String>,
+    fetched: usize,
+    returned: usize,
+    seen: HashSet<String>,
+    done: bool
+}
+
+/// Search to page through
+#[derive(Clone)]
+enum Source {
+    Sql(Query),
+    Text(String)
+}
+
+/// Pages implementation
+impl Pages {
+    /// Creates a Pages instance for a SQL query. The query offset is the starting position
+    /// and the query limit, if set, is the page size.
+    ///
+    /// # Arguments
+    /// * `embeddings` - embeddings instance
+    /// * `query` - SQL query
+    pub fn sql(embeddings: Embeddings, query: &Query) -> Pages {
+        let size = query.get_limit().unwrap_or(DEFAULT_LIMIT);
+        let fetched = query.get_offset().unwrap_or(0);
+
+        Pages::new(embeddings, Source::Sql(query.clone()), size, fetched)
+    }
+
+    /// Creates a Pages instance for a text query.
+    ///
+    /// # Arguments
+    /// * `embeddings` - embeddings instance
+    /// * `query` - query text
+    pub fn text(embeddings: Embeddings, query: &str) -> Pages {
+        Pages::new(embeddings, Source::Text(query.to_string()), DEFAULT_LIMIT, 0)
+    }
+
+    fn new(embeddings: Embeddings, source: Source, size: usize, fetched: usize) -> Pages {
+        Pages {
+            embeddings,
+            source,
+            size: size.max(1),
+            max: None,
+            weights: None,
+            index: None,
+            fetched,
+            returned: 0,
+            seen: HashSet::new(),
+            done: false
+        }
+    }
+
+    /// Sets the number of results per page.
+    ///
+    /// # Arguments
+    /// * `size` - page size
+    pub fn size(mut self, size: usize) -> Pages {
+        self.size = size.max(1);
+        self
+    }
+
+    /// Sets the maximum number of results returned across all pages.
+    ///
+    /// # Arguments
+    /// * `max` - maximum results
+    pub fn max(mut self, max: usize) -> Pages {
+        self.max = Some(max);
+        self
+    }
+
+    /// Sets hybrid score weights for text queries.
+    ///
+    /// # Arguments
+    /// * `weights` - hybrid score weights
+    pub fn weights(mut self, weights: f32) -> Pages {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Sets the index name for text queries.
+    ///
+    /// # Arguments
+    /// * `index` - index name
+    pub fn index(mut self, index: &str) -> Pages {
+        self.index = Some(index.to_string());
+        self
+    }
+
+    /// Number of results returned so far.
+    pub fn returned(&self) -> usize {
+        self.returned
+    }
+
+    /// Returns true when there are no more pages.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Fetches the next page. Returns None once results are exhausted or the maximum is
+    /// reached. Pages can be shorter than the page size when duplicates are removed.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<Row>>> {
+        while !self.done {
+            // Limit the last page to the remaining results
+            let remaining = self.max.map(|max| max.saturating_sub(self.returned)).unwrap_or(usize::MAX);
+            if remaining == 0 {
+                self.done = true;
+                break;
+            }
+
+            let rows = self.fetch().await?;
+            let last = rows.len() < self.size;
+
+            // Drop results returned on earlier pages
+            let mut page: Vec<Row> = rows.into_iter().filter(|row| Pages::id(row).is_none_or(|id| self.seen.insert(id))).collect();
+            page.truncate(remaining);
+
+            self.returned += page.len();
+            self.done = last;
+
+            if !page.is_empty() {
+                return Ok(Some(page));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Fetches all remaining results.
+    pub async fn all(mut self) -> Result<Vec<Row>> {
+        let mut results = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            results.extend(page);
+        }
+
+        Ok(results)
+    }
+
+    /// Converts this instance into a stream of results. Pages are fetched as the stream is
+    /// consumed and the stream ends after the first error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Row>> {
+        stream::unfold((self, Vec::new().into_iter()), |(mut pages, mut page)| async move {
+            loop {
+                if let Some(row) = page.next() {
+                    return Some((Ok(row), (pages, page)));
+                }
+
+                match pages.next_page().await {
+                    Ok(Some(rows)) => page = rows.into_iter(),
+                    Ok(None) => return None,
+                    Err(error) => {
+                        pages.done = true;
+                        return Some((Err(error), (pages, page)));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Runs the search for the next page. Returns the raw rows, which may include
+    /// previously seen results.
+    async fn fetch(&mut self) -> Result<Vec<Row>> {
+        match &self.source {
+            Source::Sql(query) => {
+                let query = query.clone().limit(self.size).offset(self.fetched);
+                let rows = self.embeddings.sql(&query).await?;
+                self.fetched += self.size;
+
+                Ok(rows)
+            }
+            Source::Text(query) => {
+                // Text search has no offset, request the results so far plus a page
+                let limit = self.fetched + self.size;
+                let rows = self.embeddings.search_rows(query, limit as i32, self.weights, self.index.as_deref()).await?;
+                let rows: Vec<Row> = rows.into_iter().skip(self.fetched).collect();
+                self.fetched = limit;
+
+                Ok(rows)
+            }
+        }
+    }
+
+    /// Gets the id of a result, if present.
+    fn id(row: &Row) -> Option<String> {
+        match row.get("id")? {
+            Value::String(id) => Some(id.clone()),
+            Value::Null => None,
+            other => Some(other.to_string())
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use serde_json::Value;
+use std::fmt;
+
+use crate::error::{Result, TxtaiError};
+
+/// Default number of results when a query doesn't set a limit
+pub const DEFAULT_LIMIT: usize = 10;
+
+/// Query definition. Builds txtai SQL similarity queries with escaped literals and identifiers.
+///
+/// `select id, text, score from txtai where similar('query') and category = 'news' limit 10`
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    select: Vec<String>,
+    similar: Vec<Similar>,
+    filters: Vec<String>,
+    group: Vec<String>,
+    order: Vec<String>,
+    limit: Option<usize>,
+    offset: Option<usize>
+}
+
+/// Query implementation
+impl Query {
+    /// Creates an empty Query. Queries select id, text and score unless columns are selected.
+    pub fn new() -> Query {
+        Query::default()
+    }
+
+    /// Adds columns to select.
+    ///
+    /// # Arguments
+    /// * `columns` - list of column names
+    pub fn select(mut self, columns: &[&str]) -> Query {
+        self.select.extend(columns.iter().map(|column| identifier(column)));
+        self
+    }
+
+    /// Adds a raw SQL expression to select, for example `count(*)`. The expression is not escaped.
+    ///
+    /// # Arguments
+    /// * `expression` - SQL expression
+    pub fn select_raw(mut self, expression: &str) -> Query {
+        self.select.push(expression.to_string());
+        self
+    }
+
+    /// Adds a similar clause. Accepts query text or a Similar clause with options.
+    ///
+    /// # Arguments
+    /// * `similar` - similar clause
+    pub fn similar(mut self, similar: impl Into<Similar>) -> Query {
+        self.similar.push(similar.into());
+        self
+    }
+
+    /// Adds a column predicate.
+    ///
+    /// # Arguments
+    /// * `column` - column name
+    /// * `op` - comparison operator
+    /// * `value` - value to compare with
+    pub fn filter(mut self, column: &str, op: Op, value: impl Into<Value>) -> Query {
+        self.filters.push(predicate(column, op, &value.into()));
+        self
+    }
+
+    /// Adds a column equality predicate.
+    ///
+    /// # Arguments
+    /// * `column` - column name
+    /// * `value` - value to compare with
+    pub fn eq(self, column: &str, value: impl Into<Value>) -> Query {
+        self.filter(column, Op::Eq, value)
+    }
+
+    /// Adds a raw SQL predicate. The predicate is not escaped.
+    ///
+    /// # Arguments
+    /// * `predicate` - SQL predicate
+    pub fn filter_raw(mut self, predicate: &str) -> Query {
+        self.filters.push(format!("({})", predicate));
+        self
+    }
+
+    /// Adds a group by column.
+    ///
+    /// # Arguments
+    /// * `column` - column name
+    pub fn group_by(mut self, column: &str) -> Query {
+        self.group.push(identifier(column));
+        self
+    }
+
+    /// Adds an ascending order by column.
+    ///
+    /// # Arguments
+    /// * `column` - column name
+    pub fn order_by(mut self, column: &str) -> Query {
+        self.order.push(identifier(column));
+        self
+    }
+
+    /// Adds a descending order by column.
+    ///
+    /// # Arguments
+    /// * `column` - column name
+    pub fn order_by_desc(mut self, column: &str) -> Query {
+        self.order.push(format!("{} desc", identifier(column)));
+        self
+    }
+
+    /// Sets the maximum number of results.
+    ///
+    /// # Arguments
+    /// * `limit` - maximum results
+    pub fn limit(mut self, limit: usize) -> Query {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets the number of results to skip.
+    ///
+    /// # Arguments
+    /// * `offset` - number of results to skip
+    pub fn offset(mut self, offset: usize) -> Query {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Gets the maximum number of results, if set.
+    pub fn get_limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Gets the number of results to skip, if set.
+    pub fn get_offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Renders this query as txtai SQL.
+    pub fn to_sql(&self) -> String {
+        let mut sql = String::from("select ");
+
+        // Select clause
+        if self.select.is_empty() {
+            sql.push_str("id, text, score");
+        }
+        else {
+            sql.push_str(&self.select.join(", "));
+        }
+
+        sql.push_str(" from txtai");
+
+        // Where clause
+        let clauses: Vec<String> = self.similar.iter().map(|x| x.to_string()).chain(self.filters.iter().cloned()).collect();
+        if !clauses.is_empty() {
+            sql.push_str(" where ");
+            sql.push_str(&clauses.join(" and "));
+        }
+
+        if !self.group.is_empty() {
+            sql.push_str(" group by ");
+            sql.push_str(&self.group.join(", "));
+        }
+
+        if !self.order.is_empty() {
+            sql.push_str(" order by ");
+            sql.push_str(&self.order.join(", "));
+        }
+
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" limit {}", limit));
+        }
+
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" offset {}", offset));
+        }
+
+        sql
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_sql())
+    }
+}
+
+/// Similar clause definition
+#[derive(Clone, Debug)]
+pub struct Similar {
+    query: String,
+    candidates: Option<usize>,
+    index: Option<String>,
+    weights: Option<f32>
+}
+
+/// Similar clause implementation
+impl Similar {
+    /// Creates a Similar clause.
+    ///
+    /// # Arguments
+    /// * `query` - query text
+    pub fn new(query: &str) -> Similar {
+        Similar {
+            query: query.to_string(),
+            candidates: None,
+            index: None,
+            weights: None
+        }
+    }
+
+    /// Sets the number of candidates to retrieve before filtering.
+    ///
+    /// # Arguments
+    /// * `candidates` - number of candidates
+    pub fn candidates(mut self, candidates: usize) -> Similar {
+        self.candidates = Some(candidates);
+        self
+    }
+
+    /// Sets the index to search.
+    ///
+    /// # Arguments
+    /// * `index` - index name
+    pub fn index(mut self, index: &str) -> Similar {
+        self.index = Some(index.to_string());
+        self
+    }
+
+    /// Sets hybrid score weights for this clause. Returns an error if weights is NaN or infinite,
+    /// which can't be rendered as SQL.
+    ///
+    /// # Arguments
+    /// * `weights` - hybrid score weights
+    pub fn weights(mut self, weights: f32) -> Result<Similar> {
+        if !weights.is_finite() {
+            return Err(TxtaiError::InvalidInput(format!("weights must be finite, found {}", weights)))
+        }
+
+        self.weights = Some(weights);
+        Ok(self)
+    }
+}
+
+impl fmt::Display for Similar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "similar({}", literal(&self.query))?;
+
+        if let Some(candidates) = self.candidates {
+            write!(f, ", {}", candidates)?;
+        }
+        if let Some(index) = &self.index {
+            write!(f, ", {}", literal(index))?;
+        }
+        if let Some(weights) = self.weights {
+            // Weights are always rendered with a decimal point to differentiate them from candidates
+            write!(f, ", {:?}", weights)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl From<&str> for Similar {
+    fn from(query: &str) -> Self {
+        Similar::new(query)
+    }
+}
+
+impl From<String> for Similar {
+    fn from(query: String) -> Self {
+        Similar::new(&query)
+    }
+}
+
+/// Comparison operators for column predicates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Like,
+    In
+}
+
+/// Builds a column predicate.
+///
+/// # Arguments
+/// * `column` - column name
+/// * `op` - comparison operator
+/// * `value` - value to compare with
+fn predicate(column: &str, op: Op, value: &Value) -> String {
+    let column = identifier(column);
+
+    match (op, value) {
+        (Op::Eq, Value::Null) => format!("{} is null", column),
+        (Op::Ne, Value::Null) => format!("{} is not null", column),
+        (Op::In, Value::Array(values)) => {
+            let values: Vec<String> = values.iter().map(value_sql).collect();
+            format!("{} in ({})", column, values.join(", "))
+        },
+        _ => {
+            let op = match op {
+                Op::Eq | Op::In => "=",
+                Op::Ne => "!=",
+                Op::Gt => ">",
+                Op::Gte => ">=",
+                Op::Lt => "<",
+                Op::Lte => "<=",
+                Op::Like => "like"
+            };
+
+            format!("{} {} {}", column, op, value_sql(value))
+        }
+    }
+}
+
+/// Renders a value as a SQL literal.
+///
+/// # Arguments
+/// * `value` - value
+fn value_sql(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => (if *b { "1" } else { "0" }).to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => literal(s),
+        other => literal(&other.to_string())
+    }
+}
+
+/// Quotes a string literal, escaping embedded single quotes.
+///
+/// # Arguments
+/// * `text` - input text
+pub fn literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Escapes a column name. Names that aren't plain identifiers are wrapped in brackets,
+/// which txtai uses for column names with spaces and special characters.
+///
+/// # Arguments
+/// * `name` - column name
+pub fn identifier(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+
+    if plain {
+        name.to_string()
+    }
+    else {
+        format!("[{}]", name.replace(['[', ']'], ""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn escapes_quotes() {
+        assert_eq!(literal("it's"), "'it''s'");
+        assert_eq!(literal("''"), "''''''");
+
+        let query = Query::new().similar("it's").eq("source", "o'brien");
+        assert_eq!(query.to_sql(), "select id, text, score from txtai where similar('it''s') and source = 'o''brien'");
+    }
+
+    #[test]
+    fn brackets_identifiers() {
+        assert_eq!(identifier("text"), "text");
+        assert_eq!(identifier("_id"), "_id");
+        assert_eq!(identifier("entry.date"), "entry.date");
+        assert_eq!(identifier("page count"), "[page count]");
+        assert_eq!(identifier("1st"), "[1st]");
+        assert_eq!(identifier("a]; drop [b"), "[a; drop b]");
+
+        let query = Query::new().select(&["id", "page count"]).order_by_desc("page count");
+        assert_eq!(query.to_sql(), "select id, [page count] from txtai order by [page count] desc");
+    }
+
+    #[test]
+    fn in_arrays() {
+        let query = Query::new().filter("category", Op::In, json!(["news", "it's", 1, true]));
+        assert_eq!(query.to_sql(), "select id, text, score from txtai where category in ('news', 'it''s', 1, 1)");
+
+        let query = Query::new().filter("category", Op::In, "news");
+        assert_eq!(query.to_sql(), "select id, text, score from txtai where category = 'news'");
+    }
+
+    #[test]
+    fn null_predicates() {
+        let query = Query::new().eq("category", Value::Null).filter("source", Op::Ne, Value::Null);
+        assert_eq!(query.to_sql(), "select id, text, score from txtai where category is null and source is not null");
+    }
+
+    #[test]
+    fn similar_options() {
+        let similar = Similar::new("query").candidates(100).index("sparse").weights(0.5).unwrap();
+        assert_eq!(similar.to_string(), "similar('query', 100, 'sparse', 0.5)");
+        assert_eq!(Similar::new("query").weights(1.0).unwrap().to_string(), "similar('query', 1.0)");
+    }
+
+    #[test]
+    fn rejects_non_finite_weights() {
+        for weights in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(matches!(Similar::new("it's").weights(weights), Err(TxtaiError::InvalidInput(_))));
+        }
+    }
+}
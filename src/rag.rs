@@ -0,0 +1,528 @@
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+pub use crate::api::{API, Result, TxtaiError};
+use crate::embeddings::{identifier, Embeddings};
+use crate::llm::{Generation, LLM};
+use crate::query::{Op, Query, Similar};
+
+/// RAG definition
+#[derive(Clone)]
+pub struct RAG {
+    api: Arc<API>
+}
+
+/// RAG implementation
+impl RAG {
+    /// Creates a RAG instance.
+    ///
+    pub fn new() -> RAG {
+        RAG {
+            api: Arc::new(API::new())
+        }
+    }
+
+    /// Creates a RAG instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    pub fn with_url(url: &str) -> RAG {
+        RAG {
+            api: Arc::new(API::with_url(url))
+        }
+    }
+
+    /// Creates a RAG instance.
+    ///
+    /// # Arguments
+    /// * `url` - API url
+    /// * `token` - API token
+    pub fn with_url_token(url: &str, token: &str) -> RAG {
+        RAG {
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a RAG instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> RAG {
+        RAG {
+            api: api.into()
+        }
+    }
+
+    /// Runs a retrieval augmented generation question. Returns the answer and, when the
+    /// pipeline provides them, the references used to answer.
+    ///
+    /// # Arguments
+    /// * `query` - question
+    /// * `options` - generation and output options
+    pub async fn rag(&self, query: &str, options: &Options) -> RagResult {
+        // Query parameters
+        let mut params = vec![("query", query.to_string())];
+
+        if let Some(maxlength) = options.maxlength {
+            params.push(("maxlength", maxlength.to_string()));
+        }
+        if let Some(output) = options.output {
+            params.push(("output", output.as_str().to_string()));
+        }
+
+        let params: Vec<(&str, &str)> = params.iter().map(|(name, value)| (*name, value.as_str())).collect();
+
+        // Execute API call
+        self.api.get_json("rag", &params).await
+    }
+
+    /// Runs a batch of retrieval augmented generation questions. Returns one answer per
+    /// question.
+    ///
+    /// # Arguments
+    /// * `queries` - list of questions
+    /// * `options` - generation and output options
+    pub async fn batchrag(&self, queries: &[&str], options: &Options) -> RagResults {
+        // Post parameters
+        let mut params = json!(options);
+        params["queries"] = json!(queries);
+
+        // Execute API call
+        self.api.post_json("batchrag", &params).await
+    }
+}
+
+impl Default for RAG {
+    fn default() -> Self {
+        RAG::new()
+    }
+}
+
+// RAG return types
+pub type RagResult = Result<Answer>;
+pub type RagResults = Result<Vec<Answer>>;
+
+/// RAG options definition. Unset options use the pipeline defaults.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Options {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxlength: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Output>
+}
+
+/// Options implementation
+impl Options {
+    /// Creates default Options.
+    pub fn new() -> Options {
+        Options::default()
+    }
+
+    /// Sets the maximum sequence length.
+    ///
+    /// # Arguments
+    /// * `maxlength` - maximum sequence length
+    pub fn maxlength(mut self, maxlength: usize) -> Options {
+        self.maxlength = Some(maxlength);
+        self
+    }
+
+    /// Sets the output format.
+    ///
+    /// # Arguments
+    /// * `output` - output format
+    pub fn output(mut self, output: Output) -> Options {
+        self.output = Some(output);
+        self
+    }
+}
+
+/// RAG output format
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Output {
+    /// Answer object
+    Default,
+    /// Answer text only
+    Flatten,
+    /// Answer object with the id of the best matching reference
+    Reference
+}
+
+/// Output implementation
+impl Output {
+    /// Gets the output format name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Output::Default => "default",
+            Output::Flatten => "flatten",
+            Output::Reference => "reference"
+        }
+    }
+}
+
+/// RAG answer with the references used to generate it
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Answer {
+    pub answer: String,
+    pub references: Vec<Reference>
+}
+
+/// Reference document, text and score are set when the pipeline returns them
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Reference {
+    pub id: String,
+    pub text: Option<String>,
+    pub score: Option<f32>
+}
+
+/// Deserializes answers returned as plain text, as `{"answer": .., "reference": id}` objects
+/// or as objects with a `references` list of ids or documents.
+impl<'de> Deserialize<'de> for Answer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Answer, D::Error> {
+        let mut data = match Value::deserialize(deserializer)? {
+            Value::String(answer) => return Ok(Answer { answer, references: Vec::new() }),
+            Value::Object(data) => data,
+            other => return Err(de::Error::custom(format!("invalid answer: {}", other)))
+        };
+
+        let answer = match data.remove("answer") {
+            Some(Value::String(answer)) => answer,
+            Some(Value::Null) | None => String::new(),
+            Some(other) => return Err(de::Error::custom(format!("invalid answer: {}", other)))
+        };
+
+        let mut references = Vec::new();
+        for value in data.remove("reference").into_iter().chain(data.remove("references").into_iter().flat_map(list)) {
+            if let Some(reference) = reference(value).map_err(de::Error::custom)? {
+                references.push(reference);
+            }
+        }
+
+        Ok(Answer { answer, references })
+    }
+}
+
+/// Converts a value to a list of values, single values become a one element list.
+fn list(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(values) => values,
+        value => vec![value]
+    }
+}
+
+/// Parses a reference from an id or a document object. Null references are skipped.
+fn reference(value: Value) -> std::result::Result<Option<Reference>, String> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(id) => Ok(Some(Reference { id, ..Default::default() })),
+        Value::Number(id) => Ok(Some(Reference { id: id.to_string(), ..Default::default() })),
+        Value::Object(document) => document_reference(document).map(Some),
+        other => Err(format!("invalid reference: {}", other))
+    }
+}
+
+/// Parses a reference from a document object.
+fn document_reference(mut document: Map<String, Value>) -> std::result::Result<Reference, String> {
+    let id = match document.remove("id") {
+        Some(Value::String(id)) => id,
+        Some(Value::Number(id)) => id.to_string(),
+        _ => return Err("reference missing id".to_string())
+    };
+
+    Ok(Reference {
+        id,
+        text: document.get("text").and_then(|x| x.as_str()).map(|x| x.to_string()),
+        score: document.get("score").and_then(|x| x.as_f64()).map(|x| x as f32)
+    })
+}
+
+/// Default prompt template for LocalRAG
+pub const DEFAULT_TEMPLATE: &str = "Answer the following question using only the context below. Only include information specifically discussed.
+
+question: {question}
+context: {context}";
+
+/// LocalRAG definition. Client-side retrieval augmented generation for deployments without
+/// the RAG pipeline. Passages are retrieved with an Embeddings similarity query, packed into
+/// a context within a budget and sent to a LLM with a prompt template.
+#[derive(Clone)]
+pub struct LocalRAG {
+    embeddings: Embeddings,
+    llm: LLM,
+    query: Query,
+    limit: usize,
+    minscore: Option<f32>,
+    weights: Option<f32>,
+    index: Option<String>,
+    budget: Budget,
+    template: String,
+    generation: Generation
+}
+
+/// LocalRAG implementation
+impl LocalRAG {
+    /// Creates a LocalRAG instance that retrieves 3 passages into a 4,000 character context.
+    ///
+    /// # Arguments
+    /// * `embeddings` - embeddings instance used for retrieval
+    /// * `llm` - LLM instance used for generation
+    pub fn new(embeddings: Embeddings, llm: LLM) -> LocalRAG {
+        LocalRAG {
+            embeddings,
+            llm,
+            query: Query::new(),
+            limit: 3,
+            minscore: None,
+            weights: None,
+            index: None,
+            budget: Budget::Characters(4000),
+            template: DEFAULT_TEMPLATE.to_string(),
+            generation: Generation::default()
+        }
+    }
+
+    /// Sets the maximum number of passages to retrieve.
+    ///
+    /// # Arguments
+    /// * `limit` - maximum passages
+    pub fn limit(mut self, limit: usize) -> LocalRAG {
+        self.limit = limit.max(1);
+        self
+    }
+
+    /// Sets the minimum score for a passage to be used.
+    ///
+    /// # Arguments
+    /// * `minscore` - minimum score
+    pub fn minscore(mut self, minscore: f32) -> LocalRAG {
+        self.minscore = Some(minscore);
+        self
+    }
+
+    /// Adds a column predicate applied when retrieving passages.
+    ///
+    /// # Arguments
+    /// * `column` - column name
+    /// * `op` - comparison operator
+    /// * `value` - value to compare with
+    pub fn filter(mut self, column: &str, op: Op, value: impl Into<Value>) -> LocalRAG {
+        self.query = self.query.filter(column, op, value);
+        self
+    }
+
+    /// Adds a raw SQL predicate applied when retrieving passages. The predicate is not escaped.
+    ///
+    /// # Arguments
+    /// * `predicate` - SQL predicate
+    pub fn filter_raw(mut self, predicate: &str) -> LocalRAG {
+        self.query = self.query.filter_raw(predicate);
+        self
+    }
+
+    /// Sets hybrid score weights for retrieval. Non-finite weights are rejected when retrieving.
+    ///
+    /// # Arguments
+    /// * `weights` - hybrid score weights
+    pub fn weights(mut self, weights: f32) -> LocalRAG {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Sets the index to retrieve passages from.
+    ///
+    /// # Arguments
+    /// * `index` - index name
+    pub fn index(mut self, index: &str) -> LocalRAG {
+        self.index = Some(index.to_string());
+        self
+    }
+
+    /// Sets the context size budget.
+    ///
+    /// # Arguments
+    /// * `budget` - context budget
+    pub fn budget(mut self, budget: Budget) -> LocalRAG {
+        self.budget = budget;
+        self
+    }
+
+    /// Sets the prompt template. Templates must contain `{question}` and `{context}`.
+    ///
+    /// # Arguments
+    /// * `template` - prompt template
+    pub fn template(mut self, template: &str) -> LocalRAG {
+        self.template = template.to_string();
+        self
+    }
+
+    /// Sets LLM generation parameters.
+    ///
+    /// # Arguments
+    /// * `generation` - generation parameters
+    pub fn generation(mut self, generation: Generation) -> LocalRAG {
+        self.generation = generation;
+        self
+    }
+
+    /// Retrieves passages for a question, ordered by descending score. Passages below the
+    /// minimum score are removed.
+    ///
+    /// # Arguments
+    /// * `question` - question
+    pub async fn retrieve(&self, question: &str) -> Result<Vec<Passage>> {
+        let mut similar = Similar::new(question);
+        if let Some(weights) = self.weights {
+            similar = similar.weights(weights)?;
+        }
+        if let Some(index) = &self.index {
+            similar = similar.index(index);
+        }
+
+        let query = self.query.clone().similar(similar).limit(self.limit);
+        let mut passages: Vec<Passage> = self.embeddings.sql(&query).await?;
+
+        passages.retain(|passage| self.minscore.is_none_or(|minscore| passage.score >= minscore));
+        passages.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(passages)
+    }
+
+    /// Builds the prompt for a question from retrieved passages. Passages are added in order
+    /// until the budget is reached, a first passage larger than the budget is truncated.
+    /// Returns the prompt and the passages it includes.
+    ///
+    /// # Arguments
+    /// * `question` - question
+    /// * `passages` - retrieved passages
+    pub fn prompt(&self, question: &str, passages: Vec<Passage>) -> Result<(String, Vec<Passage>)> {
+        if !self.template.contains("{question}") || !self.template.contains("{context}") {
+            return Err(TxtaiError::InvalidInput("template must contain {question} and {context}".to_string()));
+        }
+
+        let mut context = String::new();
+        let mut used = Vec::new();
+
+        for mut passage in passages {
+            let separator = if context.is_empty() { "" } else { "\n\n" };
+            let size = self.budget.size(&format!("{}{}{}", context, separator, passage.text));
+
+            if size > self.budget.limit() {
+                // Truncate an oversized first passage, later passages are skipped
+                if used.is_empty() {
+                    passage.text = self.budget.truncate(&passage.text);
+                    context.push_str(&passage.text);
+                    used.push(passage);
+                }
+                break;
+            }
+
+            context.push_str(separator);
+            context.push_str(&passage.text);
+            used.push(passage);
+        }
+
+        Ok((render(&self.template, question, &context), used))
+    }
+
+    /// Answers a question. Retrieves passages, builds the prompt and runs the LLM.
+    ///
+    /// # Arguments
+    /// * `question` - question
+    pub async fn answer(&self, question: &str) -> Result<Generated> {
+        let passages = self.retrieve(question).await?;
+        let (prompt, passages) = self.prompt(question, passages)?;
+        let answer = self.llm.llm(&prompt, &self.generation).await?;
+
+        Ok(Generated { answer, passages, prompt })
+    }
+}
+
+/// Fills the `{question}` and `{context}` placeholders in a template. Substituted values
+/// aren't scanned for placeholders.
+fn render(template: &str, question: &str, context: &str) -> String {
+    let mut output = String::with_capacity(template.len() + question.len() + context.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(remaining) = rest.strip_prefix("{question}") {
+            output.push_str(question);
+            rest = remaining;
+        }
+        else if let Some(remaining) = rest.strip_prefix("{context}") {
+            output.push_str(context);
+            rest = remaining;
+        }
+        else {
+            output.push('{');
+            rest = &rest[1..];
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Context size budget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Budget {
+    /// Maximum number of characters
+    Characters(usize),
+    /// Maximum number of tokens, estimated as whitespace separated words
+    Tokens(usize)
+}
+
+/// Budget implementation
+impl Budget {
+    /// Gets the budget limit.
+    pub fn limit(&self) -> usize {
+        match self {
+            Budget::Characters(limit) | Budget::Tokens(limit) => *limit
+        }
+    }
+
+    /// Measures text using this budget's unit.
+    ///
+    /// # Arguments
+    /// * `text` - input text
+    pub fn size(&self, text: &str) -> usize {
+        match self {
+            Budget::Characters(_) => text.chars().count(),
+            Budget::Tokens(_) => text.split_whitespace().count()
+        }
+    }
+
+    /// Truncates text to fit within this budget.
+    ///
+    /// # Arguments
+    /// * `text` - input text
+    pub fn truncate(&self, text: &str) -> String {
+        match self {
+            Budget::Characters(limit) => text.chars().take(*limit).collect(),
+            Budget::Tokens(limit) => text.split_whitespace().take(*limit).collect::<Vec<_>>().join(" ")
+        }
+    }
+}
+
+/// Retrieved passage
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Passage {
+    #[serde(deserialize_with = "identifier")]
+    pub id: String,
+    pub text: String,
+    pub score: f32
+}
+
+/// LocalRAG answer with the prompt and source passages used to generate it
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Generated {
+    pub answer: String,
+    pub passages: Vec<Passage>,
+    pub prompt: String
+}
@@ -0,0 +1,368 @@
+use futures::stream::{self, Stream};
+use serde_json::{Map, Value};
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+
+use crate::document::{Document, RESERVED};
+use crate::error::{Result, TxtaiError};
+
+/// Supported file formats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON object per line
+    Jsonl,
+    /// Delimited text with a header row
+    Csv {
+        delimiter: char
+    }
+}
+
+/// Reader definition. Streams records from JSONL and CSV files as documents without loading
+/// the whole file into memory.
+///
+/// `embeddings.bulk().index(Reader::jsonl().text("body").open("data.jsonl").await?).await?`
+#[derive(Clone, Debug)]
+pub struct Reader {
+    format: Format,
+    id: Option<String>,
+    text: String,
+    metadata: Option<Vec<String>>
+}
+
+/// Reader implementation
+impl Reader {
+    /// Creates a JSONL Reader.
+    pub fn jsonl() -> Reader {
+        Reader::new(Format::Jsonl)
+    }
+
+    /// Creates a comma delimited CSV Reader.
+    pub fn csv() -> Reader {
+        Reader::new(Format::Csv { delimiter: ',' })
+    }
+
+    /// Creates a Reader. Ids are read from the id field and text from the text field. All
+    /// other fields are passed through as metadata.
+    ///
+    /// # Arguments
+    /// * `format` - file format
+    pub fn new(format: Format) -> Reader {
+        Reader {
+            format,
+            id: Some("id".to_string()),
+            text: "text".to_string(),
+            metadata: None
+        }
+    }
+
+    /// Sets the CSV delimiter.
+    ///
+    /// # Arguments
+    /// * `delimiter` - field delimiter
+    pub fn delimiter(mut self, delimiter: char) -> Reader {
+        self.format = Format::Csv { delimiter };
+        self
+    }
+
+    /// Sets the id field. When None, ids are generated from the record number.
+    ///
+    /// # Arguments
+    /// * `field` - id field name
+    pub fn id(mut self, field: Option<&str>) -> Reader {
+        self.id = field.map(|x| x.to_string());
+        self
+    }
+
+    /// Sets the text field.
+    ///
+    /// # Arguments
+    /// * `field` - text field name
+    pub fn text(mut self, field: &str) -> Reader {
+        self.text = field.to_string();
+        self
+    }
+
+    /// Sets the fields passed through as metadata. When None, all fields other than the
+    /// id and text fields are passed through.
+    ///
+    /// # Arguments
+    /// * `fields` - list of metadata field names
+    pub fn metadata(mut self, fields: Option<&[&str]>) -> Reader {
+        self.metadata = fields.map(|fields| fields.iter().map(|x| x.to_string()).collect());
+        self
+    }
+
+    /// Opens a file and returns a stream of documents. Records are read as the stream is
+    /// polled. Malformed records end the stream with an error that includes the line number.
+    /// A leading UTF-8 byte order mark is ignored.
+    ///
+    /// # Arguments
+    /// * `path` - file path
+    pub async fn open(&self, path: impl AsRef<Path>) -> Result<impl Stream<Item = Result<Document>>> {
+        let mut lines = BufReader::new(File::open(path).await?).lines();
+
+        // Read CSV header
+        let header = match self.format {
+            Format::Csv { delimiter } => {
+                let (_, fields) = read_csv(&mut lines, delimiter, &mut 0).await?
+                    .ok_or_else(|| TxtaiError::InvalidInput("CSV file is missing a header row".to_string()))?;
+                Some(fields)
+            },
+            Format::Jsonl => None
+        };
+
+        let state = State { reader: self.clone(), lines, header, line: if self.format == Format::Jsonl { 0 } else { 1 }, record: 0, done: false };
+
+        Ok(stream::unfold(state, |mut state| async move {
+            if state.done {
+                return None
+            }
+
+            let result = state.next().await.transpose()?;
+            state.done = result.is_err();
+
+            Some((result, state))
+        }))
+    }
+
+    /// Converts a record into a document.
+    ///
+    /// # Arguments
+    /// * `fields` - record fields
+    /// * `record` - record number, used to generate ids
+    /// * `line` - line number, used in error messages
+    fn document(&self, mut fields: Map<String, Value>, record: usize, line: usize) -> Result<Document> {
+        let id = match &self.id {
+            Some(field) => match fields.remove(field) {
+                Some(Value::String(id)) => id,
+                Some(Value::Number(id)) => id.to_string(),
+                _ => return Err(TxtaiError::InvalidInput(format!("line {}: missing id field {}", line, field)))
+            },
+            None => record.to_string()
+        };
+
+        let text = match fields.remove(&self.text) {
+            Some(Value::String(text)) => text,
+            Some(Value::Null) | None => return Err(TxtaiError::InvalidInput(format!("line {}: missing text field {}", line, self.text))),
+            Some(value) => value.to_string()
+        };
+
+        let mut document = Document::new(&id, &text);
+
+        // Object references
+        match fields.remove("object") {
+            Some(Value::String(object)) => document = document.object(&object),
+            Some(Value::Null) | None => (),
+            Some(_) => return Err(TxtaiError::InvalidInput(format!("line {}: object field must be a string", line)))
+        }
+
+        // Metadata
+        document.metadata = match &self.metadata {
+            Some(names) => names.iter().filter_map(|name| fields.remove(name).map(|value| (name.clone(), value))).collect(),
+            None => fields
+        };
+
+        document.metadata.retain(|name, _| !RESERVED.contains(&name.as_str()));
+
+        Ok(document)
+    }
+}
+
+/// Reader stream state
+struct State {
+    reader: Reader,
+    lines: Lines<BufReader<File>>,
+    header: Option<Vec<String>>,
+    line: usize,
+    record: usize,
+    done: bool
+}
+
+/// Reader stream state implementation
+impl State {
+    /// Reads the next document. Returns None at the end of the file.
+    async fn next(&mut self) -> Result<Option<Document>> {
+        let (line, fields) = match self.reader.format {
+            Format::Jsonl => match self.read_json().await? {
+                Some(record) => record,
+                None => return Ok(None)
+            },
+            Format::Csv { delimiter } => match read_csv(&mut self.lines, delimiter, &mut self.line).await? {
+                Some((line, values)) => {
+                    let header = self.header.as_deref().unwrap_or_default();
+                    if values.len() != header.len() {
+                        return Err(TxtaiError::InvalidInput(format!("line {}: expected {} fields, found {}", line, header.len(), values.len())))
+                    }
+
+                    (line, header.iter().cloned().zip(values.into_iter().map(Value::String)).collect())
+                },
+                None => return Ok(None)
+            }
+        };
+
+        let document = self.reader.document(fields, self.record, line)?;
+        self.record += 1;
+
+        Ok(Some(document))
+    }
+
+    /// Reads the next JSON object, skipping blank lines.
+    async fn read_json(&mut self) -> Result<Option<(usize, Map<String, Value>)>> {
+        while let Some(text) = self.lines.next_line().await? {
+            self.line += 1;
+
+            let text = strip(&text, self.line);
+            if text.trim().is_empty() {
+                continue
+            }
+
+            return match serde_json::from_str(text) {
+                Ok(Value::Object(fields)) => Ok(Some((self.line, fields))),
+                Ok(_) => Err(TxtaiError::InvalidInput(format!("line {}: expected a JSON object", self.line))),
+                Err(e) => Err(TxtaiError::InvalidInput(format!("line {}: {}", self.line, e)))
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Reads the next CSV record, skipping blank lines. Quoted fields can contain delimiters,
+/// escaped quotes ("") and line breaks. Returns the starting line number and field values.
+///
+/// # Arguments
+/// * `lines` - input lines
+/// * `delimiter` - field delimiter
+/// * `line` - current line number, updated as lines are read
+async fn read_csv(lines: &mut Lines<BufReader<File>>, delimiter: char, line: &mut usize) -> Result<Option<(usize, Vec<String>)>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut start = 0;
+
+    while let Some(text) = lines.next_line().await? {
+        *line += 1;
+
+        let text = strip(&text, *line);
+        if !quoted {
+            if text.trim().is_empty() {
+                continue
+            }
+            start = *line;
+        }
+        else {
+            // Line break inside a quoted field
+            field.push('\n');
+        }
+
+        let mut chars = text.trim_end_matches('\r').chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                },
+                '"' => quoted = !quoted,
+                c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+                c => field.push(c)
+            }
+        }
+
+        if !quoted {
+            fields.push(field);
+            return Ok(Some((start, fields)))
+        }
+    }
+
+    if quoted {
+        return Err(TxtaiError::InvalidInput(format!("line {}: unterminated quoted field", start)))
+    }
+
+    Ok(None)
+}
+
+/// Strips the UTF-8 byte order mark that some editors write at the start of a file.
+///
+/// # Arguments
+/// * `text` - line text
+/// * `line` - line number
+fn strip(text: &str, line: usize) -> &str {
+    if line == 1 { text.strip_prefix('\u{feff}').unwrap_or(text) } else { text }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Writes a temporary file and returns its path.
+    fn write(name: &str, data: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("txtai-reader-{}-{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    /// Reads all documents from a file.
+    async fn read(reader: Reader, name: &str, data: &str) -> Result<Vec<Document>> {
+        let path = write(name, data);
+        let result = match reader.open(&path).await {
+            Ok(stream) => stream.try_collect().await,
+            Err(e) => Err(e)
+        };
+
+        std::fs::remove_file(path).unwrap();
+        result
+    }
+
+    #[tokio::test]
+    async fn quoted_delimiters() {
+        let documents = read(Reader::csv(), "delimiters.csv", "id,text,source\n1,\"a, b\",web\n").await.unwrap();
+        assert_eq!(documents[0].text, "a, b");
+        assert_eq!(documents[0].metadata["source"], json!("web"));
+
+        let documents = read(Reader::csv().delimiter('|'), "pipes.csv", "id|text\n1|\"a|b\"\n").await.unwrap();
+        assert_eq!(documents[0].text, "a|b");
+    }
+
+    #[tokio::test]
+    async fn escaped_quotes() {
+        let documents = read(Reader::csv(), "quotes.csv", "id,text\n1,\"say \"\"hi\"\"\"\n2,\"\"\"\"\n").await.unwrap();
+        assert_eq!(documents[0].text, "say \"hi\"");
+        assert_eq!(documents[1].text, "\"");
+    }
+
+    #[tokio::test]
+    async fn multiline_fields() {
+        let documents = read(Reader::csv(), "multiline.csv", "id,text\r\n1,\"first\r\nsecond\"\r\n2,third\r\n").await.unwrap();
+        assert_eq!(documents[0].text, "first\nsecond");
+        assert_eq!(documents[1].id, "2");
+        assert_eq!(documents[1].text, "third");
+
+        let error = read(Reader::csv(), "unterminated.csv", "id,text\n1,ok\n2,\"open\nstill open\n").await.unwrap_err();
+        assert!(matches!(error, TxtaiError::InvalidInput(message) if message == "line 3: unterminated quoted field"));
+    }
+
+    #[tokio::test]
+    async fn field_counts() {
+        let error = read(Reader::csv(), "short.csv", "id,text,source\n1,a,web\n2,b\n").await.unwrap_err();
+        assert!(matches!(error, TxtaiError::InvalidInput(message) if message == "line 3: expected 3 fields, found 2"));
+
+        let error = read(Reader::csv(), "long.csv", "id,text\n1,\"a,b\",c\n").await.unwrap_err();
+        assert!(matches!(error, TxtaiError::InvalidInput(message) if message == "line 2: expected 2 fields, found 3"));
+    }
+
+    #[tokio::test]
+    async fn byte_order_marks() {
+        let documents = read(Reader::csv(), "bom.csv", "\u{feff}id,text\n1,a\n").await.unwrap();
+        assert_eq!(documents[0].id, "1");
+        assert!(documents[0].metadata.is_empty());
+
+        let documents = read(Reader::jsonl(), "bom.jsonl", "\u{feff}{\"id\": 1, \"text\": \"a\"}\n").await.unwrap();
+        assert_eq!(documents[0].id, "1");
+        assert_eq!(documents[0].text, "a");
+    }
+}
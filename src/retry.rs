@@ -0,0 +1,149 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::TxtaiError;
+
+/// API methods that change index state. These requests are only retried when the
+/// connection failed before the request was sent, unless `idempotent_only` is disabled.
+const NON_IDEMPOTENT: [&str; 6] = ["add", "addimage", "addobject", "index", "reindex", "upsert"];
+
+/// Retry policy definition
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts per request, including the first attempt
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for any single delay
+    pub max_backoff: Duration,
+    /// Backoff growth factor applied after each attempt
+    pub multiplier: f64,
+    /// Fraction of each delay that is randomized, between 0.0 (none) and 1.0 (full jitter)
+    pub jitter: f64,
+    /// HTTP status codes that are retried
+    pub statuses: Vec<u16>,
+    /// Retry connection failures. Connections reset after the request was sent are only
+    /// retried for idempotent methods.
+    pub connect: bool,
+    /// Retry requests that timed out
+    pub timeouts: bool,
+    /// Only replay idempotent requests, non-idempotent requests are retried on connection failures only
+    pub idempotent_only: bool,
+    /// API methods considered non-idempotent
+    pub non_idempotent: Vec<String>
+}
+
+/// Retry policy implementation
+impl RetryPolicy {
+    /// Creates a policy that never retries.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Creates the default policy with a custom number of attempts.
+    ///
+    /// # Arguments
+    /// * `max_attempts` - maximum number of attempts per request, including the first attempt
+    pub fn with_attempts(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Checks if an API method can be safely replayed.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    pub fn idempotent(&self, method: &str) -> bool {
+        !self.non_idempotent.iter().any(|x| x == method)
+    }
+
+    /// Checks if a response should be retried.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `response` - API response
+    pub fn retry_response(&self, method: &str, response: &Response) -> bool {
+        (!self.idempotent_only || self.idempotent(method)) && self.statuses.contains(&response.status().as_u16())
+    }
+
+    /// Checks if a request error should be retried.
+    ///
+    /// # Arguments
+    /// * `method` - API method
+    /// * `error` - request error
+    pub fn retry_error(&self, method: &str, error: &TxtaiError) -> bool {
+        match error {
+            // Connection failures are safe to replay, the request never reached the server
+            TxtaiError::Transport(e) if e.is_connect() => self.connect,
+            // Connection resets on an open connection, for example while the server reloads a
+            // model. The request may have reached the server, so only idempotent methods are replayed.
+            TxtaiError::Transport(e) if e.is_request() || e.is_body() => self.connect && self.idempotent(method),
+            TxtaiError::Timeout { source: Some(e), .. } if e.is_connect() => self.connect,
+            TxtaiError::Timeout { .. } => self.timeouts && (!self.idempotent_only || self.idempotent(method)),
+            _ => false
+        }
+    }
+
+    /// Calculates the delay before the next attempt. A Retry-After header, when present,
+    /// takes precedence over the backoff curve.
+    ///
+    /// # Arguments
+    /// * `attempt` - number of attempts made so far, starting at 1
+    /// * `response` - response that triggered the retry, if any
+    pub fn delay(&self, attempt: u32, response: Option<&Response>) -> Duration {
+        // Server requested delay
+        let requested = response.filter(|r| r.status() == StatusCode::TOO_MANY_REQUESTS || r.status() == StatusCode::SERVICE_UNAVAILABLE)
+            .and_then(|r| r.headers().get(RETRY_AFTER))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+
+        if let Some(seconds) = requested {
+            return Duration::from_secs(seconds).min(self.max_backoff)
+        }
+
+        // Exponential backoff
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+
+        // Randomize part of the delay to spread out clients retrying at the same time
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        Duration::from_secs_f64(backoff * (1.0 - jitter * random()))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.5,
+            statuses: vec![429, 502, 503, 504],
+            connect: true,
+            timeouts: true,
+            idempotent_only: true,
+            non_idempotent: NON_IDEMPOTENT.iter().map(|x| x.to_string()).collect()
+        }
+    }
+}
+
+/// Generates a random number in [0, 1). Uses the randomly seeded std hasher, which is
+/// sufficient for spreading out retries.
+fn random() -> f64 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(nanos);
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
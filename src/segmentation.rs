@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, Texts, TextsBatch};
 
 /// Segmentation definition
 pub struct Segmentation {
-    api: API
+    api: Arc<API>
 }
 
 /// Segmentation implementation
@@ -13,7 +14,7 @@ impl Segmentation {
     /// 
     pub fn new() -> Segmentation {
         Segmentation { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Segmentation {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Segmentation {
         Segmentation {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Segmentation {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Segmentation {
         Segmentation {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Segmentation instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Segmentation {
+        Segmentation {
+            api: api.into()
         }
     }
 
@@ -47,7 +59,7 @@ impl Segmentation {
         let params = [("text", text)];
 
         // Execute API call
-        Ok(self.api.get("segment", &params).await?.json().await?)
+        self.api.get_json("segment", &params).await
     }
 
     /// Segments text into semantic units.
@@ -59,6 +71,12 @@ impl Segmentation {
         let params = json!(texts);
 
         // Execute API call
-        Ok(self.api.post("batchsegment", &params).await?.json().await?)
+        self.api.post_json("batchsegment", &params).await
+    }
+}
+
+impl Default for Segmentation {
+    fn default() -> Self {
+        Segmentation::new()
     }
 }
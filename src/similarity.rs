@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, IndexResults, IndexResultsBatch};
 
 /// Similarity definition
 pub struct Similarity {
-    api: API
+    api: Arc<API>
 }
 
 /// Similarity implementation
@@ -13,7 +14,7 @@ impl Similarity {
     /// 
     pub fn new() -> Similarity {
         Similarity { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Similarity {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Similarity {
         Similarity {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Similarity {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Similarity {
         Similarity {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Similarity instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Similarity {
+        Similarity {
+            api: api.into()
         }
     }
 
@@ -50,7 +62,7 @@ impl Similarity {
         let params = json!({"query": query, "texts": texts});
 
         // Execute API call
-        Ok(self.api.post("similarity", &params).await?.json().await?)
+        self.api.post_json("similarity", &params).await
     }
 
     /// Computes the similarity between list of queries and list of text. Returns a list
@@ -65,6 +77,12 @@ impl Similarity {
         let params = json!({"queries": queries, "texts": texts});
 
         // Execute API call
-        Ok(self.api.post("batchsimilarity", &params).await?.json().await?)
+        self.api.post_json("batchsimilarity", &params).await
+    }
+}
+
+impl Default for Similarity {
+    fn default() -> Self {
+        Similarity::new()
     }
 }
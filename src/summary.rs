@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, Strings, StringsBatch};
 
 /// Summary definition
 pub struct Summary {
-    api: API
+    api: Arc<API>
 }
 
 /// Summary implementation
@@ -13,7 +14,7 @@ impl Summary {
     /// 
     pub fn new() -> Summary {
         Summary { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Summary {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Summary {
         Summary {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Summary {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Summary {
         Summary {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Summary instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Summary {
+        Summary {
+            api: api.into()
         }
     }
 
@@ -59,7 +71,7 @@ impl Summary {
         }
 
         // Execute API call
-        Ok(self.api.get("summary", &params).await?.json().await?)
+        self.api.get_json("summary", &params).await
     }
 
     /// Runs a summarization model against a block of text.
@@ -77,6 +89,12 @@ impl Summary {
         });
 
         // Execute API call
-        Ok(self.api.post("batchsummary", &params).await?.json().await?)
+        self.api.post_json("batchsummary", &params).await
+    }
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Summary::new()
     }
 }
@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, Texts, TextsBatch};
 
 /// Textractor definition
 pub struct Textractor {
-    api: API
+    api: Arc<API>
 }
 
 /// Textractor implementation
@@ -13,7 +14,7 @@ impl Textractor {
     /// 
     pub fn new() -> Textractor {
         Textractor { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Textractor {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Textractor {
         Textractor {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Textractor {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Textractor {
         Textractor {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Textractor instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Textractor {
+        Textractor {
+            api: api.into()
         }
     }
 
@@ -47,7 +59,7 @@ impl Textractor {
         let params = [("file", file)];
 
         // Execute API call
-        Ok(self.api.get("textract", &params).await?.json().await?)
+        self.api.get_json("textract", &params).await
     }
 
     /// Extracts text from a file at path.
@@ -59,6 +71,12 @@ impl Textractor {
         let params = json!(files);
 
         // Execute API call
-        Ok(self.api.post("batchtextract", &params).await?.json().await?)
+        self.api.post_json("batchtextract", &params).await
+    }
+}
+
+impl Default for Textractor {
+    fn default() -> Self {
+        Textractor::new()
     }
 }
@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, Strings, StringsBatch};
 
 /// Transcription definition
 pub struct Transcription {
-    api: API
+    api: Arc<API>
 }
 
 /// Transcription implementation
@@ -13,7 +14,7 @@ impl Transcription {
     /// 
     pub fn new() -> Transcription {
         Transcription { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Transcription {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Transcription {
         Transcription {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Transcription {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Transcription {
         Transcription {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Transcription instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Transcription {
+        Transcription {
+            api: api.into()
         }
     }
 
@@ -46,7 +58,7 @@ impl Transcription {
         let params = [("file", file)];
 
         // Execute API call
-        Ok(self.api.get("transcribe", &params).await?.json().await?)
+        self.api.get_json("transcribe", &params).await
     }
 
     /// Transcribes audio files to text.
@@ -57,6 +69,12 @@ impl Transcription {
         let params = json!(files);
 
         // Execute API call
-        Ok(self.api.post("batchtranscribe", &params).await?.json().await?)
+        self.api.post_json("batchtranscribe", &params).await
+    }
+}
+
+impl Default for Transcription {
+    fn default() -> Self {
+        Transcription::new()
     }
 }
@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, Strings, StringsBatch};
 
 /// Translation definition
 pub struct Translation {
-    api: API
+    api: Arc<API>
 }
 
 /// Translation implementation
@@ -13,7 +14,7 @@ impl Translation {
     /// 
     pub fn new() -> Translation {
         Translation { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Translation {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Translation {
         Translation {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Translation {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Translation {
         Translation {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Translation instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Translation {
+        Translation {
+            api: api.into()
         }
     }
 
@@ -51,15 +63,15 @@ impl Translation {
         let tgt = target.unwrap_or("");
         let src = source.unwrap_or("");
 
-        if tgt != "" {
-            params.push(("target", &tgt));
+        if !tgt.is_empty() {
+            params.push(("target", tgt));
         }
-        if src != "" {
-            params.push(("source", &src));
+        if !src.is_empty() {
+            params.push(("source", src));
         }
 
         // Execute API call
-        Ok(self.api.get("translate", &params).await?.json().await?)
+        self.api.get_json("translate", &params).await
     }
 
     /// Translates text from source language into target language.
@@ -77,6 +89,12 @@ impl Translation {
         });
 
         // Execute API call
-        Ok(self.api.post("batchtranslate", &params).await?.json().await?)
+        self.api.post_json("batchtranslate", &params).await
+    }
+}
+
+impl Default for Translation {
+    fn default() -> Self {
+        Translation::new()
     }
 }
@@ -0,0 +1,429 @@
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt;
+use std::ops::Index;
+use std::slice::ChunksExact;
+
+use crate::api::IndexResult;
+use crate::error::{Result, TxtaiError};
+
+/// Number of accumulators used by the vector kernels. Independent lanes let the compiler
+/// vectorize loops with SIMD instructions.
+const LANES: usize = 8;
+
+/// Vectors definition. Stores a batch of embeddings with the same number of dimensions in a
+/// single contiguous buffer, row after row.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Vectors {
+    data: Vec<f32>,
+    dimensions: usize
+}
+
+/// Vectors implementation
+impl Vectors {
+    /// Creates an empty Vectors instance.
+    ///
+    /// # Arguments
+    /// * `dimensions` - number of dimensions per vector
+    pub fn new(dimensions: usize) -> Vectors {
+        Vectors {
+            data: Vec::new(),
+            dimensions
+        }
+    }
+
+    /// Creates a Vectors instance from a flat buffer.
+    ///
+    /// # Arguments
+    /// * `data` - vector data, row after row
+    /// * `dimensions` - number of dimensions per vector
+    pub fn from_flat(data: Vec<f32>, dimensions: usize) -> Result<Vectors> {
+        if !data.len().is_multiple_of(dimensions) {
+            return Err(TxtaiError::InvalidInput(format!("{} values can't be split into vectors with {} dimensions", data.len(), dimensions)))
+        }
+
+        Ok(Vectors { data, dimensions })
+    }
+
+    /// Creates a Vectors instance from a list of vectors. All vectors must have the same
+    /// number of dimensions.
+    ///
+    /// # Arguments
+    /// * `rows` - list of vectors
+    pub fn from_rows<R: AsRef<[f32]>>(rows: &[R]) -> Result<Vectors> {
+        let mut vectors = Vectors::new(rows.first().map_or(0, |row| row.as_ref().len()));
+        for row in rows {
+            vectors.push(row.as_ref())?;
+        }
+
+        Ok(vectors)
+    }
+
+    /// Appends a vector.
+    ///
+    /// # Arguments
+    /// * `row` - vector to append
+    pub fn push(&mut self, row: &[f32]) -> Result<()> {
+        if self.is_empty() && self.dimensions == 0 {
+            self.dimensions = row.len();
+        }
+
+        if row.len() != self.dimensions {
+            return Err(TxtaiError::InvalidInput(format!("expected {} dimensions, found {}", self.dimensions, row.len())))
+        }
+
+        self.data.extend_from_slice(row);
+        Ok(())
+    }
+
+    /// Appends all vectors from another Vectors instance.
+    ///
+    /// # Arguments
+    /// * `other` - vectors to append
+    pub fn extend(&mut self, other: &Vectors) -> Result<()> {
+        for row in other.iter() {
+            self.push(row)?;
+        }
+
+        Ok(())
+    }
+
+    /// Number of vectors.
+    pub fn len(&self) -> usize {
+        self.data.len().checked_div(self.dimensions).unwrap_or(0)
+    }
+
+    /// Returns true if there are no vectors.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Number of dimensions per vector.
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Gets a vector by position.
+    ///
+    /// # Arguments
+    /// * `index` - vector position
+    pub fn get(&self, index: usize) -> Option<&[f32]> {
+        let start = index.checked_mul(self.dimensions)?;
+        self.data.get(start..start + self.dimensions).filter(|_| self.dimensions > 0)
+    }
+
+    /// Iterates over vectors.
+    pub fn iter(&self) -> ChunksExact<'_, f32> {
+        // chunks_exact requires a non-zero size, an empty buffer yields nothing either way
+        self.data.chunks_exact(self.dimensions.max(1))
+    }
+
+    /// Gets the underlying buffer.
+    pub fn as_slice(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Converts into a list of vectors.
+    pub fn into_rows(self) -> Vec<Vec<f32>> {
+        self.iter().map(|row| row.to_vec()).collect()
+    }
+
+    /// Scales each vector to unit length in place.
+    pub fn normalize(&mut self) {
+        for row in self.data.chunks_exact_mut(self.dimensions.max(1)) {
+            normalize(row);
+        }
+    }
+
+    /// Computes the dot product between a query and each vector. Returns an error if the
+    /// query dimensions don't match.
+    ///
+    /// # Arguments
+    /// * `query` - query vector
+    pub fn dot(&self, query: &[f32]) -> Result<Vec<f32>> {
+        self.check(query)?;
+        Ok(self.iter().map(|row| product(query, row)).collect())
+    }
+
+    /// Computes the cosine similarity between a query and each vector. Returns an error if
+    /// the query dimensions don't match.
+    ///
+    /// # Arguments
+    /// * `query` - query vector
+    pub fn cosine(&self, query: &[f32]) -> Result<Vec<f32>> {
+        self.check(query)?;
+
+        let length = norm(query);
+        Ok(self.iter().map(|row| similarity(query, row, length)).collect())
+    }
+
+    /// Finds the vectors most similar to a query using cosine similarity. Returns a list of
+    /// {id: value, score: value} sorted by highest score, where id is the vector position.
+    /// Returns an error if the query dimensions don't match.
+    ///
+    /// # Arguments
+    /// * `query` - query vector
+    /// * `k` - maximum results
+    pub fn topk(&self, query: &[f32], k: usize) -> Result<Vec<IndexResult>> {
+        Ok(topk(&self.cosine(query)?, k))
+    }
+
+    /// Checks that a query has the same number of dimensions as these vectors.
+    fn check(&self, query: &[f32]) -> Result<()> {
+        if !self.is_empty() && query.len() != self.dimensions {
+            return Err(TxtaiError::InvalidInput(format!("expected {} dimensions, found {}", self.dimensions, query.len())))
+        }
+
+        Ok(())
+    }
+
+    /// Computes the mean of all vectors. Returns None when empty.
+    pub fn centroid(&self) -> Option<Vec<f32>> {
+        if self.is_empty() {
+            return None
+        }
+
+        let mut centroid = vec![0.0; self.dimensions];
+        for row in self.iter() {
+            for (x, y) in centroid.iter_mut().zip(row) {
+                *x += y;
+            }
+        }
+
+        let count = self.len() as f32;
+        for x in centroid.iter_mut() {
+            *x /= count;
+        }
+
+        Some(centroid)
+    }
+}
+
+impl Index<usize> for Vectors {
+    type Output = [f32];
+
+    fn index(&self, index: usize) -> &[f32] {
+        self.get(index).expect("vector index out of range")
+    }
+}
+
+impl<'a> IntoIterator for &'a Vectors {
+    type Item = &'a [f32];
+    type IntoIter = ChunksExact<'a, f32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Serialize for Vectors {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for row in self.iter() {
+            seq.serialize_element(row)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Vectors {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_seq(VectorsVisitor)
+    }
+}
+
+/// Deserializes a list of lists directly into a contiguous buffer
+struct VectorsVisitor;
+
+impl<'de> Visitor<'de> for VectorsVisitor {
+    type Value = Vectors;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of vectors with the same number of dimensions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Vectors, A::Error> {
+        let mut vectors = Vectors::default();
+        while let Some(row) = seq.next_element::<Vec<f32>>()? {
+            vectors.push(&row).map_err(de::Error::custom)?;
+        }
+
+        Ok(vectors)
+    }
+}
+
+/// Computes the dot product of two vectors. Returns an error if the vectors have a
+/// different number of dimensions.
+///
+/// # Arguments
+/// * `a` - first vector
+/// * `b` - second vector
+pub fn dot(a: &[f32], b: &[f32]) -> Result<f32> {
+    dimensions(a, b)?;
+    Ok(product(a, b))
+}
+
+/// Computes the Euclidean length of a vector.
+///
+/// # Arguments
+/// * `a` - vector
+pub fn norm(a: &[f32]) -> f32 {
+    product(a, a).sqrt()
+}
+
+/// Scales a vector to unit length in place. Zero vectors are left unchanged.
+///
+/// # Arguments
+/// * `a` - vector
+pub fn normalize(a: &mut [f32]) {
+    let norm = norm(a);
+    if norm > 0.0 {
+        for x in a.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+/// Computes the cosine similarity of two vectors. Returns 0 if either vector is all zeros.
+/// Returns an error if the vectors have a different number of dimensions.
+///
+/// # Arguments
+/// * `a` - first vector
+/// * `b` - second vector
+pub fn cosine(a: &[f32], b: &[f32]) -> Result<f32> {
+    dimensions(a, b)?;
+    Ok(similarity(a, b, norm(a)))
+}
+
+/// Checks that two vectors have the same number of dimensions.
+fn dimensions(a: &[f32], b: &[f32]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(TxtaiError::InvalidInput(format!("vectors must have the same number of dimensions, found {} and {}", a.len(), b.len())))
+    }
+
+    Ok(())
+}
+
+/// Computes the cosine similarity of two vectors of equal length, `length` is the norm of `a`.
+fn similarity(a: &[f32], b: &[f32], length: f32) -> f32 {
+    let norms = length * norm(b);
+    if norms > 0.0 { product(a, b) / norms } else { 0.0 }
+}
+
+/// Computes the dot product of two vectors of equal length using 8 accumulators.
+fn product(a: &[f32], b: &[f32]) -> f32 {
+    let (x, y) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let remainder: f32 = x.remainder().iter().zip(y.remainder()).map(|(a, b)| a * b).sum();
+
+    let mut sums = [0.0f32; LANES];
+    for (x, y) in x.zip(y) {
+        for ((sum, a), b) in sums.iter_mut().zip(x).zip(y) {
+            *sum += a * b;
+        }
+    }
+
+    sums.iter().sum::<f32>() + remainder
+}
+
+/// Selects the k highest scores. Returns a list of {id: value, score: value} sorted by
+/// highest score, where id is the score position.
+///
+/// # Arguments
+/// * `scores` - list of scores
+/// * `k` - maximum results
+pub fn topk(scores: &[f32], k: usize) -> Vec<IndexResult> {
+    let mut results: Vec<IndexResult> = scores.iter().enumerate().map(|(id, score)| IndexResult { id, score: *score }).collect();
+    let descending = |a: &IndexResult, b: &IndexResult| b.score.total_cmp(&a.score);
+
+    // Partition the top k results before sorting them
+    if k < results.len() {
+        if k == 0 {
+            return Vec::new()
+        }
+        results.select_nth_unstable_by(k - 1, descending);
+        results.truncate(k);
+    }
+
+    results.sort_by(descending);
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the ids from a list of results.
+    fn ids(results: &[IndexResult]) -> Vec<usize> {
+        results.iter().map(|x| x.id).collect()
+    }
+
+    #[test]
+    fn push_dimensions() {
+        let mut vectors = Vectors::default();
+        vectors.push(&[1.0, 2.0]).unwrap();
+        vectors.push(&[3.0, 4.0]).unwrap();
+
+        assert_eq!(vectors.dimensions(), 2);
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(&vectors[1], &[3.0, 4.0]);
+        assert!(matches!(vectors.push(&[5.0]), Err(TxtaiError::InvalidInput(_))));
+        assert_eq!(vectors.len(), 2);
+
+        let mut vectors = Vectors::new(3);
+        assert!(vectors.push(&[1.0, 2.0]).is_err());
+        assert!(vectors.is_empty());
+
+        assert!(Vectors::from_rows(&[vec![1.0, 2.0], vec![3.0]]).is_err());
+    }
+
+    #[test]
+    fn from_flat_dimensions() {
+        let vectors = Vectors::from_flat(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3).unwrap();
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors.get(1), Some(&[4.0, 5.0, 6.0][..]));
+        assert_eq!(vectors.get(2), None);
+
+        assert!(Vectors::from_flat(vec![1.0, 2.0, 3.0], 2).is_err());
+        assert!(Vectors::from_flat(vec![1.0], 0).is_err());
+
+        let empty = Vectors::from_flat(Vec::new(), 0).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.get(0), None);
+        assert_eq!(empty.iter().count(), 0);
+    }
+
+    #[test]
+    fn query_dimensions() {
+        let vectors = Vectors::from_rows(&[[1.0, 0.0], [0.0, 1.0]]).unwrap();
+        assert_eq!(vectors.dot(&[2.0, 3.0]).unwrap(), vec![2.0, 3.0]);
+        assert!(vectors.dot(&[1.0]).is_err());
+        assert!(vectors.cosine(&[1.0, 0.0, 0.0]).is_err());
+        assert!(vectors.topk(&[1.0], 1).is_err());
+
+        assert!(dot(&[1.0], &[1.0, 2.0]).is_err());
+        assert!(cosine(&[1.0], &[1.0, 2.0]).is_err());
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 2.0]).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn dot_remainder() {
+        // 11 dimensions covers a full chunk of lanes and a remainder
+        let a: Vec<f32> = (1..=11).map(|x| x as f32).collect();
+        assert_eq!(dot(&a, &a).unwrap(), 506.0);
+        assert_eq!(norm(&[3.0, 4.0]), 5.0);
+    }
+
+    #[test]
+    fn topk_limits() {
+        let scores = [0.1, 0.9, 0.5, 0.7];
+        assert!(topk(&scores, 0).is_empty());
+        assert_eq!(ids(&topk(&scores, 2)), vec![1, 3]);
+        assert_eq!(ids(&topk(&scores, 4)), vec![1, 3, 2, 0]);
+        assert_eq!(ids(&topk(&scores, 10)), vec![1, 3, 2, 0]);
+        assert!(topk(&[], 3).is_empty());
+
+        let vectors = Vectors::from_rows(&[[1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]).unwrap();
+        assert_eq!(ids(&vectors.topk(&[1.0, 0.1], 2).unwrap()), vec![0, 2]);
+        assert!(vectors.topk(&[1.0, 0.1], 0).unwrap().is_empty());
+    }
+}
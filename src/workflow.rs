@@ -1,10 +1,11 @@
 use serde_json::json;
+use std::sync::Arc;
 
 pub use crate::api::{API, Texts, TextsBatch};
 
 /// Workflow definition
 pub struct Workflow {
-    api: API
+    api: Arc<API>
 }
 
 /// Workflow implementation
@@ -13,7 +14,7 @@ impl Workflow {
     /// 
     pub fn new() -> Workflow {
         Workflow { 
-            api: API::new()
+            api: Arc::new(API::new())
         }
     }
 
@@ -23,7 +24,7 @@ impl Workflow {
     /// * `url` - API url
     pub fn with_url(url: &str) -> Workflow {
         Workflow {
-            api: API::with_url(url) 
+            api: Arc::new(API::with_url(url)) 
         }
     }
 
@@ -34,7 +35,18 @@ impl Workflow {
     /// * `token` - API token
     pub fn with_url_token(url: &str, token: &str) -> Workflow {
         Workflow {
-            api: API::with_url_token(url, token)
+            api: Arc::new(API::with_url_token(url, token))
+        }
+    }
+
+    /// Creates a Workflow instance from an existing API or Arc<API>. Instances created from
+    /// the same Arc share configuration and the connection pool.
+    ///
+    /// # Arguments
+    /// * `api` - API instance
+    pub fn with_api(api: impl Into<Arc<API>>) -> Workflow {
+        Workflow {
+            api: api.into()
         }
     }
 
@@ -50,6 +62,12 @@ impl Workflow {
         });
 
         // Execute API call
-        Ok(self.api.post("workflow", &params).await?.json().await?)
+        self.api.post_json("workflow", &params).await
+    }
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        Workflow::new()
     }
 }